@@ -0,0 +1,80 @@
+#![no_std]
+//! Contract admin bootstrap shared by the GiveHub contracts, so the
+//! deployer check behaves the same everywhere. Each contract maps
+//! `AdminError` into its own error enum.
+use soroban_sdk::{panic_with_error, symbol_short, Address, BytesN, Env, Error};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum AdminError {
+    Unauthorized,
+    AdminNotSet,
+}
+
+/// Sets the first admin. Only the account that deployed the contract, as
+/// proven by re-deriving the contract address from `deployer` and `salt`,
+/// may do so, and only once.
+pub fn init_admin<E>(env: &Env, deployer: Address, salt: BytesN<32>, admin: Address)
+where
+    E: From<AdminError> + Into<Error>,
+{
+    if admin_address(env).is_some() {
+        panic_with_error!(env, E::from(AdminError::Unauthorized));
+    }
+    deployer.require_auth();
+    if deployed_address(env, deployer, salt) != env.current_contract_address() {
+        panic_with_error!(env, E::from(AdminError::Unauthorized));
+    }
+    admin.require_auth();
+    env.storage()
+        .instance()
+        .set(&symbol_short!("admin"), &admin);
+}
+
+/// Hands the admin role to `admin`, with the consent of both the current
+/// and the new admin.
+pub fn set_admin<E>(env: &Env, admin: Address)
+where
+    E: From<AdminError> + Into<Error>,
+{
+    let current = admin_address(env)
+        .unwrap_or_else(|| panic_with_error!(env, E::from(AdminError::AdminNotSet)));
+    current.require_auth();
+    admin.require_auth();
+    env.storage()
+        .instance()
+        .set(&symbol_short!("admin"), &admin);
+}
+
+pub fn require_admin<E>(env: &Env, admin: &Address)
+where
+    E: From<AdminError> + Into<Error>,
+{
+    admin.require_auth();
+    let current = admin_address(env)
+        .unwrap_or_else(|| panic_with_error!(env, E::from(AdminError::AdminNotSet)));
+    if current != *admin {
+        panic_with_error!(env, E::from(AdminError::Unauthorized));
+    }
+}
+
+pub fn admin_address(env: &Env) -> Option<Address> {
+    env.storage().instance().get(&symbol_short!("admin"))
+}
+
+pub fn deployed_address(env: &Env, deployer: Address, salt: BytesN<32>) -> Address {
+    env.deployer()
+        .with_address(deployer, salt)
+        .deployed_address()
+}
+
+/// The address a contract deployed by `deployer` with an all-`salt` salt
+/// gets, for registering test contracts where `init_admin` accepts them.
+#[cfg(feature = "testutils")]
+pub fn test_deployed_address(env: &Env, deployer: &Address, salt: u8) -> Address {
+    deployed_address(env, deployer.clone(), test_salt(env, salt))
+}
+
+#[cfg(feature = "testutils")]
+pub fn test_salt(env: &Env, salt: u8) -> BytesN<32> {
+    BytesN::from_array(env, &[salt; 32])
+}
@@ -1,9 +1,19 @@
 #![no_std]
+use givehub_admin::AdminError;
 use soroban_sdk::{
-    contract, contracterror, contractimpl, contracttype, panic_with_error, Address, BytesN, Env,
-    String,
+    contract, contracterror, contractimpl, contracttype, panic_with_error, symbol_short, token,
+    vec, xdr::ToXdr, Address, Bytes, BytesN, Env, Error, IntoVal, InvokeError, Map, String, Symbol,
+    Vec,
 };
 
+pub const MAX_PAGE_SIZE: u32 = 50;
+pub const MAX_DECIMALS: u32 = 38;
+pub const MAX_BATCH_SIZE: u32 = 50;
+pub const MAX_BPS: u32 = 10_000;
+pub const DEFAULT_STALE_SECS: u64 = 365 * 24 * 60 * 60;
+pub const SECS_PER_DAY: u64 = 24 * 60 * 60;
+pub const MAX_SNAPSHOTS: u32 = 16;
+
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 #[contracttype]
 pub enum CampaignStatus {
@@ -12,6 +22,14 @@ pub enum CampaignStatus {
     Funded,
     Completed,
     Cancelled,
+    Expired,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum Visibility {
+    Public,
+    Unlisted,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -21,13 +39,74 @@ pub struct Campaign {
     pub title: String,
     pub description: String,
     pub target_amount: i128,
+    pub hard_cap: Option<i128>,
+    pub impact_unit_cost: i128,
+    pub impact_unit_label: String,
     pub current_amount: i128,
     pub released_amount: i128,
+    pub endowed_amount: i128,
+    pub token: Address,
+    pub decimals: u32,
+    pub balances: Map<Address, i128>,
     pub creator: Address,
+    pub pending_owner: Option<Address>,
     pub donation_contract: Option<Address>,
     pub verification_contract: Option<Address>,
+    pub endowment_address: Option<Address>,
+    pub endowment_bps: u32,
+    pub notify_contract: Option<Address>,
     pub status: CampaignStatus,
+    pub visibility: Visibility,
+    pub paused: bool,
+    pub compact_donations: bool,
+    pub auto_activate: bool,
     pub created_at: u64,
+    pub activated_at: Option<u64>,
+    pub last_donation_at: Option<u64>,
+    pub goal_reached_at: Option<u64>,
+}
+
+/// A point-in-time record of a campaign's balance. `hash` is the sha256 of
+/// `campaign_id (32 bytes) || current_amount (16 bytes) || released_amount
+/// (16 bytes) || ledger_seq (4 bytes) || timestamp (8 bytes)`, with every
+/// integer encoded big-endian, so auditors can re-derive it off-chain.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct Attestation {
+    pub campaign_id: BytesN<32>,
+    pub current_amount: i128,
+    pub released_amount: i128,
+    pub ledger_seq: u32,
+    pub timestamp: u64,
+    pub hash: BytesN<32>,
+}
+
+/// A self-contained copy of a campaign for backups and migrations.
+/// Milestones and donations stay in their own contracts; `reference_hashes`
+/// carries the digests those contracts report for the campaign under
+/// `milestone` and `donations`, omitting any contract that is unset or does
+/// not report one. `hash` is the sha256 of the
+/// XDR encoding of every other field as a tuple. It is an accidental
+/// corruption check only: anyone who edits the bundle can recompute it.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct CampaignExport {
+    pub campaign: Campaign,
+    pub status_history: Vec<(CampaignStatus, u64)>,
+    pub vesting_schedule: Vec<(u64, i128)>,
+    pub vested_tranches: u32,
+    pub reference_hashes: Map<Symbol, BytesN<32>>,
+    pub hash: BytesN<32>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct Invariants {
+    pub amounts_non_negative: bool,
+    pub released_within_raised: bool,
+    pub within_hard_cap: bool,
+    pub balances_match: bool,
+    pub status_consistent: bool,
 }
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
@@ -41,6 +120,67 @@ pub enum CampaignError {
     Unauthorized = 5,
     InsufficientFunds = 6,
     ContractsNotConfigured = 7,
+    InvalidHardCap = 8,
+    HardCapExceeded = 9,
+    InvalidImpactCost = 10,
+    AdminNotSet = 11,
+    UnsupportedDecimals = 12,
+    NoPendingTransfer = 13,
+    BatchTooLarge = 14,
+    Paused = 15,
+    TargetOutOfBand = 16,
+    NotStale = 17,
+    InvalidEndowment = 18,
+    TooEarlyToCancel = 19,
+    CampaignExists = 20,
+    InvalidExport = 21,
+    InvalidSchedule = 22,
+    VestingEnabled = 23,
+    TokenDenied = 24,
+    TokenNotAccepted = 25,
+    NoDefaultToken = 26,
+}
+
+impl CampaignError {
+    fn symbol(self) -> Symbol {
+        match self {
+            CampaignError::CampaignNotFound => symbol_short!("no_camp"),
+            CampaignError::InvalidTarget => symbol_short!("bad_tgt"),
+            CampaignError::NotDraft => symbol_short!("not_draft"),
+            CampaignError::NotActive => symbol_short!("inactive"),
+            CampaignError::Unauthorized => symbol_short!("unauth"),
+            CampaignError::InsufficientFunds => symbol_short!("no_funds"),
+            CampaignError::ContractsNotConfigured => symbol_short!("no_cfg"),
+            CampaignError::InvalidHardCap => symbol_short!("bad_cap"),
+            CampaignError::HardCapExceeded => symbol_short!("over_cap"),
+            CampaignError::InvalidImpactCost => symbol_short!("bad_cost"),
+            CampaignError::AdminNotSet => symbol_short!("no_admin"),
+            CampaignError::UnsupportedDecimals => symbol_short!("decimals"),
+            CampaignError::NoPendingTransfer => symbol_short!("no_xfer"),
+            CampaignError::BatchTooLarge => symbol_short!("batch"),
+            CampaignError::Paused => symbol_short!("paused"),
+            CampaignError::TargetOutOfBand => symbol_short!("band"),
+            CampaignError::NotStale => symbol_short!("not_stale"),
+            CampaignError::InvalidEndowment => symbol_short!("bad_endow"),
+            CampaignError::TooEarlyToCancel => symbol_short!("too_early"),
+            CampaignError::CampaignExists => symbol_short!("exists"),
+            CampaignError::InvalidExport => symbol_short!("bad_exprt"),
+            CampaignError::InvalidSchedule => symbol_short!("bad_sched"),
+            CampaignError::VestingEnabled => symbol_short!("vesting"),
+            CampaignError::TokenDenied => symbol_short!("denied"),
+            CampaignError::TokenNotAccepted => symbol_short!("bad_token"),
+            CampaignError::NoDefaultToken => symbol_short!("no_token"),
+        }
+    }
+}
+
+impl From<AdminError> for CampaignError {
+    fn from(error: AdminError) -> Self {
+        match error {
+            AdminError::Unauthorized => CampaignError::Unauthorized,
+            AdminError::AdminNotSet => CampaignError::AdminNotSet,
+        }
+    }
 }
 
 #[contract]
@@ -48,6 +188,85 @@ pub struct CampaignContract;
 
 #[contractimpl]
 impl CampaignContract {
+    pub fn init_admin(env: Env, deployer: Address, salt: BytesN<32>, admin: Address) {
+        givehub_admin::init_admin::<CampaignError>(&env, deployer, salt, admin);
+    }
+
+    pub fn set_admin(env: Env, admin: Address) {
+        givehub_admin::set_admin::<CampaignError>(&env, admin);
+    }
+
+    pub fn set_target_band(env: Env, admin: Address, token: Address, band: Option<(i128, i128)>) {
+        Self::require_admin(&env, &admin);
+
+        let key = (symbol_short!("band"), token);
+        match band {
+            Some((min, max)) => {
+                if min <= 0 || min > max {
+                    panic_with_error!(&env, CampaignError::InvalidTarget);
+                }
+                env.storage().persistent().set(&key, &(min, max));
+            }
+            None => env.storage().persistent().remove(&key),
+        }
+    }
+
+    pub fn target_band(env: Env, token: Address) -> Option<(i128, i128)> {
+        env.storage()
+            .persistent()
+            .get(&(symbol_short!("band"), token))
+    }
+
+    pub fn set_token_denied(env: Env, admin: Address, token: Address, denied: bool) {
+        Self::require_admin(&env, &admin);
+
+        let key = (symbol_short!("denied"), token);
+        if denied == env.storage().persistent().has(&key) {
+            return;
+        }
+        let count: u32 = env
+            .storage()
+            .instance()
+            .get(&symbol_short!("denied_n"))
+            .unwrap_or(0);
+        if denied {
+            env.storage().persistent().set(&key, &true);
+            env.storage()
+                .instance()
+                .set(&symbol_short!("denied_n"), &(count + 1));
+        } else {
+            env.storage().persistent().remove(&key);
+            env.storage()
+                .instance()
+                .set(&symbol_short!("denied_n"), &(count - 1));
+        }
+    }
+
+    pub fn set_default_token(env: Env, admin: Address, token: Address) {
+        Self::require_admin(&env, &admin);
+        env.storage()
+            .instance()
+            .set(&symbol_short!("def_token"), &token);
+    }
+
+    pub fn default_token(env: Env) -> Option<Address> {
+        env.storage().instance().get(&symbol_short!("def_token"))
+    }
+
+    pub fn is_token_denied(env: Env, token: Address) -> bool {
+        let count: u32 = env
+            .storage()
+            .instance()
+            .get(&symbol_short!("denied_n"))
+            .unwrap_or(0);
+        count > 0
+            && env
+                .storage()
+                .persistent()
+                .has(&(symbol_short!("denied"), token))
+    }
+
+    #[allow(clippy::too_many_arguments)]
     pub fn initialize(
         env: Env,
         creator: Address,
@@ -55,28 +274,60 @@ impl CampaignContract {
         title: String,
         description: String,
         target_amount: i128,
+        token: Option<Address>,
+        auto_activate: bool,
     ) -> Campaign {
         creator.require_auth();
 
         if target_amount <= 0 {
             panic_with_error!(&env, CampaignError::InvalidTarget);
         }
+        let token = token
+            .or_else(|| Self::default_token(env.clone()))
+            .unwrap_or_else(|| panic_with_error!(&env, CampaignError::NoDefaultToken));
+        if Self::is_token_denied(env.clone(), token.clone()) {
+            panic_with_error!(&env, CampaignError::TokenDenied);
+        }
+        let decimals = token::Client::new(&env, &token).decimals();
+        if decimals > MAX_DECIMALS {
+            panic_with_error!(&env, CampaignError::UnsupportedDecimals);
+        }
+        Self::check_target_band(&env, &token, target_amount);
 
         let campaign = Campaign {
             id: campaign_id.clone(),
             title,
             description,
             target_amount,
+            hard_cap: None,
+            impact_unit_cost: 0,
+            impact_unit_label: String::from_str(&env, ""),
             current_amount: 0,
             released_amount: 0,
+            endowed_amount: 0,
+            token,
+            decimals,
+            balances: Map::new(&env),
             creator: creator.clone(),
+            pending_owner: None,
             donation_contract: None,
             verification_contract: None,
+            endowment_address: None,
+            endowment_bps: 0,
+            notify_contract: None,
             status: CampaignStatus::Draft,
+            visibility: Visibility::Public,
+            paused: false,
+            compact_donations: false,
+            auto_activate,
             created_at: env.ledger().timestamp(),
+            activated_at: None,
+            last_donation_at: None,
+            goal_reached_at: None,
         };
 
         env.storage().persistent().set(&campaign_id, &campaign);
+        Self::record_status(&env, &campaign);
         campaign
     }
 
@@ -100,6 +351,172 @@ impl CampaignContract {
         campaign
     }
 
+    pub fn propose_ownership_transfer(
+        env: Env,
+        creator: Address,
+        campaign_id: BytesN<32>,
+        new_owner: Address,
+    ) -> Campaign {
+        creator.require_auth();
+
+        let mut campaign = Self::get_campaign(&env, &campaign_id);
+        if campaign.creator != creator {
+            panic_with_error!(&env, CampaignError::Unauthorized);
+        }
+
+        campaign.pending_owner = Some(new_owner);
+        Self::save_campaign(&env, &campaign_id, &campaign);
+        campaign
+    }
+
+    pub fn accept_ownership(env: Env, new_owner: Address, campaign_id: BytesN<32>) -> Campaign {
+        new_owner.require_auth();
+
+        let mut campaign = Self::get_campaign(&env, &campaign_id);
+        match campaign.pending_owner {
+            Some(ref pending) if *pending == new_owner => {}
+            Some(_) => panic_with_error!(&env, CampaignError::Unauthorized),
+            None => panic_with_error!(&env, CampaignError::NoPendingTransfer),
+        }
+
+        campaign.creator = new_owner;
+        campaign.pending_owner = None;
+        Self::save_campaign(&env, &campaign_id, &campaign);
+        campaign
+    }
+
+    pub fn set_hard_cap(
+        env: Env,
+        creator: Address,
+        campaign_id: BytesN<32>,
+        hard_cap: Option<i128>,
+    ) -> Campaign {
+        creator.require_auth();
+
+        let mut campaign = Self::get_campaign(&env, &campaign_id);
+        if campaign.creator != creator {
+            panic_with_error!(&env, CampaignError::Unauthorized);
+        }
+        if let Some(cap) = hard_cap {
+            if cap < campaign.target_amount || cap < campaign.current_amount {
+                panic_with_error!(&env, CampaignError::InvalidHardCap);
+            }
+        }
+
+        campaign.hard_cap = hard_cap;
+        Self::save_campaign(&env, &campaign_id, &campaign);
+        campaign
+    }
+
+    pub fn set_impact(
+        env: Env,
+        creator: Address,
+        campaign_id: BytesN<32>,
+        unit_cost: i128,
+        unit_label: String,
+    ) -> Campaign {
+        creator.require_auth();
+
+        let mut campaign = Self::get_campaign(&env, &campaign_id);
+        if campaign.creator != creator {
+            panic_with_error!(&env, CampaignError::Unauthorized);
+        }
+        if unit_cost < 0 {
+            panic_with_error!(&env, CampaignError::InvalidImpactCost);
+        }
+
+        campaign.impact_unit_cost = unit_cost;
+        campaign.impact_unit_label = unit_label;
+        Self::save_campaign(&env, &campaign_id, &campaign);
+        campaign
+    }
+
+    pub fn set_visibility(
+        env: Env,
+        creator: Address,
+        campaign_id: BytesN<32>,
+        visibility: Visibility,
+    ) -> Campaign {
+        creator.require_auth();
+
+        let mut campaign = Self::get_campaign(&env, &campaign_id);
+        if campaign.creator != creator {
+            panic_with_error!(&env, CampaignError::Unauthorized);
+        }
+
+        let was_listed = Self::is_listed(&campaign);
+        campaign.visibility = visibility;
+        Self::update_active_index(&env, &campaign, was_listed);
+        Self::save_campaign(&env, &campaign_id, &campaign);
+        campaign
+    }
+
+    pub fn set_endowment(
+        env: Env,
+        creator: Address,
+        campaign_id: BytesN<32>,
+        endowment_address: Option<Address>,
+        endowment_bps: u32,
+    ) -> Campaign {
+        creator.require_auth();
+
+        let mut campaign = Self::get_campaign(&env, &campaign_id);
+        if campaign.creator != creator {
+            panic_with_error!(&env, CampaignError::Unauthorized);
+        }
+        if campaign.status != CampaignStatus::Draft {
+            panic_with_error!(&env, CampaignError::NotDraft);
+        }
+        if endowment_bps > MAX_BPS || (endowment_address.is_none() && endowment_bps > 0) {
+            panic_with_error!(&env, CampaignError::InvalidEndowment);
+        }
+
+        campaign.endowment_address = endowment_address;
+        campaign.endowment_bps = endowment_bps;
+        Self::save_campaign(&env, &campaign_id, &campaign);
+        campaign
+    }
+
+    pub fn set_notify_contract(
+        env: Env,
+        creator: Address,
+        campaign_id: BytesN<32>,
+        notify_contract: Option<Address>,
+    ) -> Campaign {
+        creator.require_auth();
+
+        let mut campaign = Self::get_campaign(&env, &campaign_id);
+        if campaign.creator != creator {
+            panic_with_error!(&env, CampaignError::Unauthorized);
+        }
+
+        campaign.notify_contract = notify_contract;
+        Self::save_campaign(&env, &campaign_id, &campaign);
+        campaign
+    }
+
+    pub fn set_compact_donations(
+        env: Env,
+        creator: Address,
+        campaign_id: BytesN<32>,
+        enabled: bool,
+    ) -> Campaign {
+        creator.require_auth();
+
+        let mut campaign = Self::get_campaign(&env, &campaign_id);
+        if campaign.creator != creator {
+            panic_with_error!(&env, CampaignError::Unauthorized);
+        }
+
+        if campaign.status != CampaignStatus::Draft {
+            panic_with_error!(&env, CampaignError::NotDraft);
+        }
+
+        campaign.compact_donations = enabled;
+        Self::save_campaign(&env, &campaign_id, &campaign);
+        campaign
+    }
+
     pub fn activate(env: Env, creator: Address, campaign_id: BytesN<32>) -> Campaign {
         creator.require_auth();
 
@@ -111,26 +528,62 @@ impl CampaignContract {
             panic_with_error!(&env, CampaignError::NotDraft);
         }
 
-        campaign.status = CampaignStatus::Active;
+        Self::set_status(&env, &mut campaign, CampaignStatus::Active);
         Self::save_campaign(&env, &campaign_id, &campaign);
         campaign
     }
 
-    pub fn add_donation(env: Env, campaign_id: BytesN<32>, amount: i128) -> Campaign {
+    pub fn pause(env: Env, creator: Address, campaign_id: BytesN<32>) -> Campaign {
+        Self::set_paused(&env, creator, campaign_id, true)
+    }
+
+    pub fn unpause(env: Env, creator: Address, campaign_id: BytesN<32>) -> Campaign {
+        Self::set_paused(&env, creator, campaign_id, false)
+    }
+
+    pub fn add_donation(
+        env: Env,
+        campaign_id: BytesN<32>,
+        token: Address,
+        amount: i128,
+    ) -> Campaign {
         let mut campaign = Self::get_campaign(&env, &campaign_id);
         match campaign.donation_contract {
             Some(ref contract) => contract.require_auth(),
             None => panic_with_error!(&env, CampaignError::ContractsNotConfigured),
         }
+        if campaign.status == CampaignStatus::Draft && campaign.auto_activate {
+            Self::set_status(&env, &mut campaign, CampaignStatus::Active);
+        }
         if campaign.status != CampaignStatus::Active && campaign.status != CampaignStatus::Funded {
             panic_with_error!(&env, CampaignError::NotActive);
         }
+        if campaign.paused {
+            panic_with_error!(&env, CampaignError::Paused);
+        }
+        if token != campaign.token {
+            panic_with_error!(&env, CampaignError::TokenNotAccepted);
+        }
+        let endowed = endowment_share(amount, campaign.endowment_bps);
+        let amount = amount - endowed;
+        if let Some(cap) = campaign.hard_cap {
+            if campaign.current_amount + amount > cap {
+                panic_with_error!(&env, CampaignError::HardCapExceeded);
+            }
+        }
 
         campaign.current_amount += amount;
+        campaign.endowed_amount += endowed;
+        campaign.last_donation_at = Some(env.ledger().timestamp());
+        let balance = campaign.balances.get(token.clone()).unwrap_or(0);
+        campaign.balances.set(token, balance + amount);
+        if campaign.goal_reached_at.is_none() && campaign.current_amount >= campaign.target_amount {
+            campaign.goal_reached_at = Some(env.ledger().timestamp());
+        }
         if campaign.current_amount >= campaign.target_amount
             && campaign.status == CampaignStatus::Active
         {
-            campaign.status = CampaignStatus::Funded;
+            Self::set_status(&env, &mut campaign, CampaignStatus::Funded);
         }
 
         Self::save_campaign(&env, &campaign_id, &campaign);
@@ -143,58 +596,298 @@ impl CampaignContract {
             Some(ref contract) => contract.require_auth(),
             None => panic_with_error!(&env, CampaignError::ContractsNotConfigured),
         }
-        let available = campaign.current_amount - campaign.released_amount;
-        if available < amount {
-            panic_with_error!(&env, CampaignError::InsufficientFunds);
+        if !Self::vesting_schedule(env.clone(), campaign_id).is_empty() {
+            panic_with_error!(&env, CampaignError::VestingEnabled);
         }
 
-        campaign.released_amount += amount;
-        if campaign.released_amount >= campaign.target_amount {
-            campaign.status = CampaignStatus::Completed;
+        Self::release(&env, &mut campaign, amount);
+        campaign
+    }
+
+    pub fn cancel_by_verification(env: Env, campaign_id: BytesN<32>) -> Campaign {
+        let mut campaign = Self::get_campaign(&env, &campaign_id);
+        match campaign.verification_contract {
+            Some(ref contract) => contract.require_auth(),
+            None => panic_with_error!(&env, CampaignError::ContractsNotConfigured),
+        }
+        if !matches!(
+            campaign.status,
+            CampaignStatus::Draft | CampaignStatus::Active | CampaignStatus::Funded
+        ) {
+            panic_with_error!(&env, CampaignError::NotActive);
         }
 
+        Self::set_status(&env, &mut campaign, CampaignStatus::Cancelled);
         Self::save_campaign(&env, &campaign_id, &campaign);
         campaign
     }
 
-    pub fn cancel(env: Env, creator: Address, campaign_id: BytesN<32>) -> Campaign {
+    pub fn clone_campaign(
+        env: Env,
+        creator: Address,
+        source_id: BytesN<32>,
+        new_id: BytesN<32>,
+    ) -> Campaign {
         creator.require_auth();
 
-        let mut campaign = Self::get_campaign(&env, &campaign_id);
-        if campaign.creator != creator {
+        let source = Self::get_campaign(&env, &source_id);
+        if source.creator != creator {
             panic_with_error!(&env, CampaignError::Unauthorized);
         }
+        if env.storage().persistent().has(&new_id) {
+            panic_with_error!(&env, CampaignError::CampaignExists);
+        }
+        if Self::is_token_denied(env.clone(), source.token.clone()) {
+            panic_with_error!(&env, CampaignError::TokenDenied);
+        }
+        Self::check_target_band(&env, &source.token, source.target_amount);
 
-        campaign.status = CampaignStatus::Cancelled;
-        Self::save_campaign(&env, &campaign_id, &campaign);
+        let campaign = Campaign {
+            id: new_id.clone(),
+            current_amount: 0,
+            released_amount: 0,
+            endowed_amount: 0,
+            balances: Map::new(&env),
+            pending_owner: None,
+            notify_contract: None,
+            status: CampaignStatus::Draft,
+            paused: false,
+            created_at: env.ledger().timestamp(),
+            activated_at: None,
+            last_donation_at: None,
+            goal_reached_at: None,
+            ..source
+        };
+
+        env.storage().persistent().set(&new_id, &campaign);
+        Self::record_status(&env, &campaign);
+        let schedule = Self::vesting_schedule(env.clone(), source_id);
+        if !schedule.is_empty() {
+            env.storage()
+                .persistent()
+                .set(&(symbol_short!("vesting"), new_id), &schedule);
+        }
         campaign
     }
 
-    pub fn get(env: Env, campaign_id: BytesN<32>) -> Campaign {
-        Self::get_campaign(&env, &campaign_id)
-    }
+    pub fn set_vesting_schedule(
+        env: Env,
+        creator: Address,
+        campaign_id: BytesN<32>,
+        schedule: Vec<(u64, i128)>,
+    ) -> Campaign {
+        creator.require_auth();
 
-    pub fn status(env: Env, campaign_id: BytesN<32>) -> CampaignStatus {
         let campaign = Self::get_campaign(&env, &campaign_id);
-        campaign.status
-    }
+        if campaign.creator != creator {
+            panic_with_error!(&env, CampaignError::Unauthorized);
+        }
+        if campaign.status != CampaignStatus::Draft {
+            panic_with_error!(&env, CampaignError::NotDraft);
+        }
 
-    pub fn is_active(env: Env, campaign_id: BytesN<32>) -> bool {
-        let campaign = Self::get_campaign(&env, &campaign_id);
-        matches!(
-            campaign.status,
-            CampaignStatus::Active | CampaignStatus::Funded
-        )
+        let mut total = 0;
+        let mut last_unlock = 0;
+        for (unlock_time, amount) in schedule.iter() {
+            if amount <= 0 || unlock_time < last_unlock {
+                panic_with_error!(&env, CampaignError::InvalidSchedule);
+            }
+            total += amount;
+            last_unlock = unlock_time;
+        }
+        if !schedule.is_empty() && total != campaign.target_amount {
+            panic_with_error!(&env, CampaignError::InvalidSchedule);
+        }
+
+        env.storage()
+            .persistent()
+            .set(&(symbol_short!("vesting"), campaign_id), &schedule);
+        campaign
     }
 
-    pub fn donation_contract(env: Env, campaign_id: BytesN<32>) -> Option<Address> {
-        let campaign = Self::get_campaign(&env, &campaign_id);
-        campaign.donation_contract
+    pub fn vesting_schedule(env: Env, campaign_id: BytesN<32>) -> Vec<(u64, i128)> {
+        env.storage()
+            .persistent()
+            .get(&(symbol_short!("vesting"), campaign_id))
+            .unwrap_or_else(|| vec![&env])
     }
 
-    pub fn verification_contract(env: Env, campaign_id: BytesN<32>) -> Option<Address> {
-        let campaign = Self::get_campaign(&env, &campaign_id);
-        campaign.verification_contract
+    pub fn vested_tranches(env: Env, campaign_id: BytesN<32>) -> u32 {
+        env.storage()
+            .persistent()
+            .get(&(symbol_short!("vested"), campaign_id))
+            .unwrap_or(0)
+    }
+
+    pub fn release_vested(env: Env, creator: Address, campaign_id: BytesN<32>) -> i128 {
+        creator.require_auth();
+
+        let mut campaign = Self::get_campaign(&env, &campaign_id);
+        if campaign.creator != creator {
+            panic_with_error!(&env, CampaignError::Unauthorized);
+        }
+        if campaign.status != CampaignStatus::Active && campaign.status != CampaignStatus::Funded {
+            panic_with_error!(&env, CampaignError::NotActive);
+        }
+
+        let schedule = Self::vesting_schedule(env.clone(), campaign_id.clone());
+        let released = Self::vested_tranches(env.clone(), campaign_id.clone());
+        let now = env.ledger().timestamp();
+        let mut unlocked = released;
+        let mut amount = 0;
+        while let Some((unlock_time, tranche)) = schedule.get(unlocked) {
+            if unlock_time > now {
+                break;
+            }
+            amount += tranche;
+            unlocked += 1;
+        }
+        if amount == 0 {
+            return 0;
+        }
+
+        Self::release(&env, &mut campaign, amount);
+        env.storage()
+            .persistent()
+            .set(&(symbol_short!("vested"), campaign_id), &unlocked);
+        amount
+    }
+
+    pub fn cancel(env: Env, creator: Address, campaign_id: BytesN<32>) -> Campaign {
+        creator.require_auth();
+
+        let mut campaign = Self::get_campaign(&env, &campaign_id);
+        if campaign.creator != creator {
+            panic_with_error!(&env, CampaignError::Unauthorized);
+        }
+        if let Some(activated_at) = campaign.activated_at {
+            let received = campaign.current_amount + campaign.endowed_amount > 0;
+            let min_active_secs = Self::min_active_secs(env.clone());
+            if received && env.ledger().timestamp() < activated_at.saturating_add(min_active_secs) {
+                panic_with_error!(&env, CampaignError::TooEarlyToCancel);
+            }
+        }
+
+        Self::set_status(&env, &mut campaign, CampaignStatus::Cancelled);
+        Self::save_campaign(&env, &campaign_id, &campaign);
+        campaign
+    }
+
+    pub fn emergency_shutdown(env: Env, admin: Address, campaign_id: BytesN<32>) -> Campaign {
+        Self::require_admin(&env, &admin);
+
+        let mut campaign = Self::get_campaign(&env, &campaign_id);
+        if campaign.status == CampaignStatus::Completed
+            || campaign.status == CampaignStatus::Cancelled
+        {
+            panic_with_error!(&env, CampaignError::NotActive);
+        }
+        Self::set_status(&env, &mut campaign, CampaignStatus::Cancelled);
+        Self::save_campaign(&env, &campaign_id, &campaign);
+
+        env.events()
+            .publish((symbol_short!("shutdown"), campaign_id), admin);
+        campaign
+    }
+
+    pub fn set_min_active_secs(env: Env, admin: Address, secs: u64) {
+        Self::require_admin(&env, &admin);
+        env.storage()
+            .instance()
+            .set(&symbol_short!("min_act"), &secs);
+    }
+
+    pub fn min_active_secs(env: Env) -> u64 {
+        env.storage()
+            .instance()
+            .get(&symbol_short!("min_act"))
+            .unwrap_or(0)
+    }
+
+    pub fn set_stale_window(env: Env, admin: Address, secs: u64) {
+        Self::require_admin(&env, &admin);
+        env.storage().instance().set(&symbol_short!("stale"), &secs);
+    }
+
+    pub fn stale_window(env: Env) -> u64 {
+        env.storage()
+            .instance()
+            .get(&symbol_short!("stale"))
+            .unwrap_or(DEFAULT_STALE_SECS)
+    }
+
+    pub fn reap_stale(env: Env, campaign_id: BytesN<32>) -> Campaign {
+        let mut campaign = Self::get_campaign(&env, &campaign_id);
+        if campaign.status != CampaignStatus::Active {
+            panic_with_error!(&env, CampaignError::NotActive);
+        }
+        let last_activity = campaign.last_donation_at.unwrap_or(campaign.created_at);
+        if env.ledger().timestamp() - last_activity < Self::stale_window(env.clone()) {
+            panic_with_error!(&env, CampaignError::NotStale);
+        }
+
+        Self::set_status(&env, &mut campaign, CampaignStatus::Expired);
+        Self::save_campaign(&env, &campaign_id, &campaign);
+
+        env.events()
+            .publish((symbol_short!("expired"), campaign_id), last_activity);
+        campaign
+    }
+
+    pub fn get(env: Env, campaign_id: BytesN<32>) -> Campaign {
+        Self::get_campaign(&env, &campaign_id)
+    }
+
+    pub fn get_many(env: Env, ids: Vec<BytesN<32>>) -> Vec<Option<Campaign>> {
+        if ids.len() > MAX_BATCH_SIZE {
+            panic_with_error!(&env, CampaignError::BatchTooLarge);
+        }
+
+        let mut campaigns = vec![&env];
+        for id in ids.iter() {
+            campaigns.push_back(env.storage().persistent().get(&id));
+        }
+        campaigns
+    }
+
+    pub fn statuses(env: Env, ids: Vec<BytesN<32>>) -> Vec<Option<CampaignStatus>> {
+        if ids.len() > MAX_BATCH_SIZE {
+            panic_with_error!(&env, CampaignError::BatchTooLarge);
+        }
+
+        let mut statuses = vec![&env];
+        for id in ids.iter() {
+            let campaign: Option<Campaign> = env.storage().persistent().get(&id);
+            statuses.push_back(campaign.map(|campaign| campaign.status));
+        }
+        statuses
+    }
+
+    pub fn status(env: Env, campaign_id: BytesN<32>) -> CampaignStatus {
+        let campaign = Self::get_campaign(&env, &campaign_id);
+        campaign.status
+    }
+
+    pub fn status_history(env: Env, campaign_id: BytesN<32>) -> Vec<(CampaignStatus, u64)> {
+        env.storage()
+            .persistent()
+            .get(&(symbol_short!("history"), campaign_id))
+            .unwrap_or_else(|| vec![&env])
+    }
+
+    pub fn is_active(env: Env, campaign_id: BytesN<32>) -> bool {
+        let campaign = Self::get_campaign(&env, &campaign_id);
+        Self::is_active_status(campaign.status) && !campaign.paused
+    }
+
+    pub fn donation_contract(env: Env, campaign_id: BytesN<32>) -> Option<Address> {
+        let campaign = Self::get_campaign(&env, &campaign_id);
+        campaign.donation_contract
+    }
+
+    pub fn verification_contract(env: Env, campaign_id: BytesN<32>) -> Option<Address> {
+        let campaign = Self::get_campaign(&env, &campaign_id);
+        campaign.verification_contract
     }
 
     pub fn creator(env: Env, campaign_id: BytesN<32>) -> Address {
@@ -202,11 +895,406 @@ impl CampaignContract {
         campaign.creator
     }
 
+    pub fn balance(env: Env, campaign_id: BytesN<32>, token: Address) -> i128 {
+        let campaign = Self::get_campaign(&env, &campaign_id);
+        campaign.balances.get(token).unwrap_or(0)
+    }
+
     pub fn available_funds(env: Env, campaign_id: BytesN<32>) -> i128 {
         let campaign = Self::get_campaign(&env, &campaign_id);
         campaign.current_amount - campaign.released_amount
     }
 
+    pub fn is_goal_reached(env: Env, campaign_id: BytesN<32>) -> bool {
+        let campaign = Self::get_campaign(&env, &campaign_id);
+        campaign.current_amount >= campaign.target_amount
+    }
+
+    pub fn funding_velocity(env: Env, campaign_id: BytesN<32>) -> i128 {
+        let campaign = Self::get_campaign(&env, &campaign_id);
+        let elapsed = env.ledger().timestamp().saturating_sub(campaign.created_at);
+        if elapsed == 0 {
+            return 0;
+        }
+        match campaign.current_amount.checked_mul(SECS_PER_DAY as i128) {
+            Some(scaled) => scaled / elapsed as i128,
+            None => {
+                (campaign.current_amount / elapsed as i128).saturating_mul(SECS_PER_DAY as i128)
+            }
+        }
+    }
+
+    pub fn projected_completion(env: Env, campaign_id: BytesN<32>) -> Option<u64> {
+        let campaign = Self::get_campaign(&env, &campaign_id);
+        let now = env.ledger().timestamp();
+        let remaining = campaign.target_amount - campaign.current_amount;
+        if remaining <= 0 {
+            return Some(now);
+        }
+        let elapsed = now.saturating_sub(campaign.created_at);
+        if elapsed == 0 || campaign.current_amount <= 0 {
+            return None;
+        }
+        let secs = remaining.checked_mul(elapsed as i128)? / campaign.current_amount;
+        now.checked_add(u64::try_from(secs).ok()?)
+    }
+
+    pub fn active_campaigns_paged(env: Env, start: u32, limit: u32) -> Vec<BytesN<32>> {
+        let end = start
+            .saturating_add(limit.min(MAX_PAGE_SIZE))
+            .min(Self::active_count(&env));
+        let mut page = vec![&env];
+        for slot in start..end {
+            let id: BytesN<32> = env
+                .storage()
+                .persistent()
+                .get(&(symbol_short!("active"), slot))
+                .unwrap();
+            page.push_back(id);
+        }
+        page
+    }
+
+    pub fn projected_impact(env: Env, campaign_id: BytesN<32>) -> i128 {
+        let campaign = Self::get_campaign(&env, &campaign_id);
+        if campaign.impact_unit_cost == 0 {
+            return 0;
+        }
+        campaign.current_amount / campaign.impact_unit_cost
+    }
+
+    pub fn format_amount(env: Env, campaign_id: BytesN<32>, raw: i128) -> String {
+        let campaign = Self::get_campaign(&env, &campaign_id);
+        let scale = 10u128.pow(campaign.decimals);
+        let whole = raw.unsigned_abs() / scale;
+        let mut fraction = raw.unsigned_abs() % scale;
+
+        let mut buf = [0u8; 80];
+        let mut len = 0;
+        if raw < 0 {
+            buf[0] = b'-';
+            len = 1;
+        }
+        len += write_digits(&mut buf[len..], whole, 1);
+        if fraction != 0 {
+            let mut width = campaign.decimals as usize;
+            while fraction.is_multiple_of(10) {
+                fraction /= 10;
+                width -= 1;
+            }
+            buf[len] = b'.';
+            len += 1;
+            len += write_digits(&mut buf[len..], fraction, width);
+        }
+        String::from_bytes(&env, &buf[..len])
+    }
+
+    pub fn invariants(env: Env, campaign_id: BytesN<32>) -> Invariants {
+        let campaign = Self::get_campaign(&env, &campaign_id);
+        let balances_total: i128 = campaign.balances.values().iter().sum();
+        let status_consistent = match campaign.status {
+            CampaignStatus::Draft => campaign.current_amount == 0,
+            CampaignStatus::Active => campaign.current_amount < campaign.target_amount,
+            CampaignStatus::Funded => campaign.current_amount >= campaign.target_amount,
+            CampaignStatus::Completed => campaign.released_amount >= campaign.target_amount,
+            CampaignStatus::Cancelled | CampaignStatus::Expired => true,
+        };
+
+        Invariants {
+            amounts_non_negative: campaign.current_amount >= 0 && campaign.released_amount >= 0,
+            released_within_raised: campaign.released_amount <= campaign.current_amount,
+            within_hard_cap: campaign
+                .hard_cap
+                .is_none_or(|cap| campaign.current_amount <= cap),
+            balances_match: balances_total == campaign.current_amount,
+            status_consistent,
+        }
+    }
+
+    /// Stores the current campaign state keyed by ledger sequence. At most
+    /// `MAX_SNAPSHOTS` are kept per campaign: once full, the oldest snapshot
+    /// is dropped, and a second snapshot in the same ledger replaces the
+    /// first.
+    pub fn snapshot(env: Env, creator: Address, campaign_id: BytesN<32>) -> u32 {
+        creator.require_auth();
+
+        let campaign = Self::get_campaign(&env, &campaign_id);
+        if campaign.creator != creator {
+            panic_with_error!(&env, CampaignError::Unauthorized);
+        }
+
+        let key = (symbol_short!("snaps"), campaign_id);
+        let mut snapshots: Vec<(u32, Campaign)> = env
+            .storage()
+            .persistent()
+            .get(&key)
+            .unwrap_or_else(|| vec![&env]);
+        let ledger_seq = env.ledger().sequence();
+        if snapshots
+            .last()
+            .is_some_and(|(last_seq, _)| last_seq == ledger_seq)
+        {
+            snapshots.pop_back();
+        }
+        if snapshots.len() >= MAX_SNAPSHOTS {
+            snapshots.pop_front();
+        }
+        snapshots.push_back((ledger_seq, campaign));
+        env.storage().persistent().set(&key, &snapshots);
+        ledger_seq
+    }
+
+    pub fn get_campaign_at(env: Env, campaign_id: BytesN<32>, ledger_seq: u32) -> Option<Campaign> {
+        let snapshots: Vec<(u32, Campaign)> = env
+            .storage()
+            .persistent()
+            .get(&(symbol_short!("snaps"), campaign_id))
+            .unwrap_or_else(|| vec![&env]);
+        snapshots
+            .iter()
+            .rev()
+            .find(|(snapshot_seq, _)| *snapshot_seq <= ledger_seq)
+            .map(|(_, campaign)| campaign)
+    }
+
+    pub fn export(env: Env, campaign_id: BytesN<32>) -> CampaignExport {
+        let campaign = Self::get_campaign(&env, &campaign_id);
+        let mut reference_hashes = Map::new(&env);
+        let sources = [
+            (
+                symbol_short!("milestone"),
+                &campaign.verification_contract,
+                "milestones_digest",
+            ),
+            (
+                symbol_short!("donations"),
+                &campaign.donation_contract,
+                "donations_digest",
+            ),
+        ];
+        for (name, contract, fn_name) in sources {
+            if let Some(hash) = Self::contract_digest(&env, contract, fn_name, &campaign_id) {
+                reference_hashes.set(name, hash);
+            }
+        }
+
+        let mut export = CampaignExport {
+            campaign,
+            status_history: Self::status_history(env.clone(), campaign_id.clone()),
+            vesting_schedule: Self::vesting_schedule(env.clone(), campaign_id.clone()),
+            vested_tranches: Self::vested_tranches(env.clone(), campaign_id),
+            reference_hashes,
+            hash: BytesN::from_array(&env, &[0; 32]),
+        };
+        export.hash = Self::export_hash(&env, &export);
+        export
+    }
+
+    pub fn import(env: Env, admin: Address, export: CampaignExport) -> Campaign {
+        Self::require_admin(&env, &admin);
+
+        let campaign = export.campaign.clone();
+        if env.storage().persistent().has(&campaign.id) {
+            panic_with_error!(&env, CampaignError::CampaignExists);
+        }
+        if Self::export_hash(&env, &export) != export.hash {
+            panic_with_error!(&env, CampaignError::InvalidExport);
+        }
+        if Self::is_token_denied(env.clone(), campaign.token.clone()) {
+            panic_with_error!(&env, CampaignError::TokenDenied);
+        }
+        Self::check_target_band(&env, &campaign.token, campaign.target_amount);
+
+        Self::save_campaign(&env, &campaign.id, &campaign);
+        env.storage().persistent().set(
+            &(symbol_short!("history"), campaign.id.clone()),
+            &export.status_history,
+        );
+        if !export.vesting_schedule.is_empty() {
+            env.storage().persistent().set(
+                &(symbol_short!("vesting"), campaign.id.clone()),
+                &export.vesting_schedule,
+            );
+        }
+        if export.vested_tranches > 0 {
+            env.storage().persistent().set(
+                &(symbol_short!("vested"), campaign.id.clone()),
+                &export.vested_tranches,
+            );
+        }
+        Self::update_active_index(&env, &campaign, false);
+        campaign
+    }
+
+    pub fn attest_balance(env: Env, campaign_id: BytesN<32>) -> Attestation {
+        let campaign = Self::get_campaign(&env, &campaign_id);
+        let ledger_seq = env.ledger().sequence();
+        let timestamp = env.ledger().timestamp();
+
+        let mut data = Bytes::from(campaign_id.clone());
+        data.extend_from_array(&campaign.current_amount.to_be_bytes());
+        data.extend_from_array(&campaign.released_amount.to_be_bytes());
+        data.extend_from_array(&ledger_seq.to_be_bytes());
+        data.extend_from_array(&timestamp.to_be_bytes());
+
+        Attestation {
+            campaign_id,
+            current_amount: campaign.current_amount,
+            released_amount: campaign.released_amount,
+            ledger_seq,
+            timestamp,
+            hash: env.crypto().sha256(&data).into(),
+        }
+    }
+
+    pub fn describe_error(_env: Env, code: u32) -> Symbol {
+        match CampaignError::try_from(Error::from_contract_error(code)) {
+            Ok(error) => error.symbol(),
+            Err(_) => symbol_short!("unknown"),
+        }
+    }
+
+    fn export_hash(env: &Env, export: &CampaignExport) -> BytesN<32> {
+        let data = (
+            export.campaign.clone(),
+            export.status_history.clone(),
+            export.vesting_schedule.clone(),
+            export.vested_tranches,
+            export.reference_hashes.clone(),
+        )
+            .to_xdr(env);
+        env.crypto().sha256(&data).into()
+    }
+
+    fn contract_digest(
+        env: &Env,
+        contract: &Option<Address>,
+        fn_name: &str,
+        campaign_id: &BytesN<32>,
+    ) -> Option<BytesN<32>> {
+        env.try_invoke_contract::<BytesN<32>, InvokeError>(
+            contract.as_ref()?,
+            &Symbol::new(env, fn_name),
+            (campaign_id.clone(),).into_val(env),
+        )
+        .ok()?
+        .ok()
+    }
+
+    fn check_target_band(env: &Env, token: &Address, target_amount: i128) {
+        if let Some((min, max)) = Self::target_band(env.clone(), token.clone()) {
+            if target_amount < min || target_amount > max {
+                panic_with_error!(env, CampaignError::TargetOutOfBand);
+            }
+        }
+    }
+
+    fn require_admin(env: &Env, admin: &Address) {
+        givehub_admin::require_admin::<CampaignError>(env, admin);
+    }
+
+    fn set_paused(env: &Env, creator: Address, campaign_id: BytesN<32>, paused: bool) -> Campaign {
+        creator.require_auth();
+
+        let mut campaign = Self::get_campaign(env, &campaign_id);
+        if campaign.creator != creator {
+            panic_with_error!(env, CampaignError::Unauthorized);
+        }
+
+        campaign.paused = paused;
+        Self::save_campaign(env, &campaign_id, &campaign);
+        campaign
+    }
+
+    fn set_status(env: &Env, campaign: &mut Campaign, status: CampaignStatus) {
+        if campaign.status == status {
+            return;
+        }
+        let was_listed = Self::is_listed(campaign);
+        campaign.status = status;
+        if status == CampaignStatus::Active && campaign.activated_at.is_none() {
+            campaign.activated_at = Some(env.ledger().timestamp());
+        }
+        Self::update_active_index(env, campaign, was_listed);
+        Self::record_status(env, campaign);
+    }
+
+    fn record_status(env: &Env, campaign: &Campaign) {
+        let key = (symbol_short!("history"), campaign.id.clone());
+        let mut history: Vec<(CampaignStatus, u64)> = env
+            .storage()
+            .persistent()
+            .get(&key)
+            .unwrap_or_else(|| vec![env]);
+        history.push_back((campaign.status, env.ledger().timestamp()));
+        env.storage().persistent().set(&key, &history);
+    }
+
+    /// Listed campaigns occupy dense slots `("active", n)` with the reverse
+    /// mapping in `("act_pos", id)`, so a transition touches a handful of
+    /// entries and removal swaps the last slot into the freed one.
+    fn update_active_index(env: &Env, campaign: &Campaign, was_listed: bool) {
+        let now_listed = Self::is_listed(campaign);
+        if was_listed == now_listed {
+            return;
+        }
+        let storage = env.storage().persistent();
+        let count = Self::active_count(env);
+        let pos_key = (symbol_short!("act_pos"), campaign.id.clone());
+        if now_listed {
+            storage.set(&(symbol_short!("active"), count), &campaign.id);
+            storage.set(&pos_key, &count);
+            storage.set(&symbol_short!("active_n"), &(count + 1));
+        } else if let Some(slot) = storage.get::<_, u32>(&pos_key) {
+            let last = count - 1;
+            if slot != last {
+                let moved: BytesN<32> = storage.get(&(symbol_short!("active"), last)).unwrap();
+                storage.set(&(symbol_short!("active"), slot), &moved);
+                storage.set(&(symbol_short!("act_pos"), moved), &slot);
+            }
+            storage.remove(&(symbol_short!("active"), last));
+            storage.remove(&pos_key);
+            storage.set(&symbol_short!("active_n"), &last);
+        }
+    }
+
+    fn release(env: &Env, campaign: &mut Campaign, amount: i128) {
+        let available = campaign.current_amount - campaign.released_amount;
+        if available < amount {
+            panic_with_error!(env, CampaignError::InsufficientFunds);
+        }
+
+        campaign.released_amount += amount;
+        if campaign.released_amount >= campaign.target_amount {
+            Self::set_status(env, campaign, CampaignStatus::Completed);
+        }
+
+        Self::save_campaign(env, &campaign.id, campaign);
+
+        if let Some(ref listener) = campaign.notify_contract {
+            let _ = env.try_invoke_contract::<(), InvokeError>(
+                listener,
+                &Symbol::new(env, "on_funds_released"),
+                (campaign.id.clone(), amount).into_val(env),
+            );
+        }
+    }
+
+    fn is_listed(campaign: &Campaign) -> bool {
+        Self::is_active_status(campaign.status) && campaign.visibility == Visibility::Public
+    }
+
+    fn is_active_status(status: CampaignStatus) -> bool {
+        matches!(status, CampaignStatus::Active | CampaignStatus::Funded)
+    }
+
+    fn active_count(env: &Env) -> u32 {
+        env.storage()
+            .persistent()
+            .get(&symbol_short!("active_n"))
+            .unwrap_or(0)
+    }
+
     fn get_campaign(env: &Env, campaign_id: &BytesN<32>) -> Campaign {
         env.storage()
             .persistent()
@@ -219,29 +1307,141 @@ impl CampaignContract {
     }
 }
 
+pub fn endowment_share(amount: i128, endowment_bps: u32) -> i128 {
+    let bps = endowment_bps as i128;
+    let scale = MAX_BPS as i128;
+    amount / scale * bps + amount % scale * bps / scale
+}
+
+fn write_digits(buf: &mut [u8], mut value: u128, width: usize) -> usize {
+    let mut digits = [0u8; 39];
+    let mut len = 0;
+    while value > 0 || len < width {
+        digits[len] = b'0' + (value % 10) as u8;
+        value /= 10;
+        len += 1;
+    }
+    for i in 0..len {
+        buf[i] = digits[len - 1 - i];
+    }
+    len
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
-    use soroban_sdk::{testutils::Address as _, Env, String};
+    use givehub_admin::{test_deployed_address, test_salt};
+    use soroban_sdk::{
+        testutils::{Address as _, Events, Ledger, MockAuth, MockAuthInvoke},
+        Env, IntoVal, String,
+    };
 
-    #[test]
-    fn test_campaign_lifecycle() {
+    #[contract]
+    pub struct Listener;
+
+    #[contractimpl]
+    impl Listener {
+        pub fn on_funds_released(env: Env, campaign_id: BytesN<32>, amount: i128) {
+            if env.storage().instance().has(&symbol_short!("broken")) {
+                panic!("listener unavailable");
+            }
+            env.storage()
+                .instance()
+                .set(&symbol_short!("last"), &(campaign_id, amount));
+        }
+
+        pub fn last(env: Env) -> Option<(BytesN<32>, i128)> {
+            env.storage().instance().get(&symbol_short!("last"))
+        }
+
+        pub fn break_listener(env: Env) {
+            env.storage()
+                .instance()
+                .set(&symbol_short!("broken"), &true);
+        }
+    }
+
+    struct Setup<'a> {
+        env: Env,
+        client: CampaignContractClient<'a>,
+        deployer: Address,
+        creator: Address,
+        token: Address,
+        campaign_id: BytesN<32>,
+    }
+
+    impl Setup<'_> {
+        fn init_admin(&self, admin: &Address) {
+            self.client
+                .init_admin(&self.deployer, &test_salt(&self.env, 0), admin);
+        }
+    }
+
+    fn setup<'a>(target: i128) -> Setup<'a> {
         let env = Env::default();
         env.mock_all_auths();
-        let contract_id = env.register_contract(None, CampaignContract);
+        let deployer = Address::generate(&env);
+        let contract_id = env.register_contract(
+            Some(&test_deployed_address(&env, &deployer, 0)),
+            CampaignContract,
+        );
         let client = CampaignContractClient::new(&env, &contract_id);
 
         let creator = Address::generate(&env);
-        let campaign_id = BytesN::from_array(&env, &[0; 32]);
-
-        let campaign = client.initialize(
+        let token = env
+            .register_stellar_asset_contract_v2(Address::generate(&env))
+            .address();
+        let campaign_id = BytesN::from_array(&env, &[7; 32]);
+        client.initialize(
             &creator,
             &campaign_id,
             &String::from_str(&env, "Test Campaign"),
             &String::from_str(&env, "Test Description"),
-            &1000,
+            &target,
+            &Some(token.clone()),
+            &false,
         );
-        assert_eq!(campaign.status, CampaignStatus::Draft);
+        client.set_authorized_contracts(
+            &creator,
+            &campaign_id,
+            &Some(Address::generate(&env)),
+            &Some(Address::generate(&env)),
+        );
+        client.activate(&creator, &campaign_id);
+
+        Setup {
+            env,
+            client,
+            deployer,
+            creator,
+            token,
+            campaign_id,
+        }
+    }
+
+    #[test]
+    fn test_campaign_lifecycle() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register_contract(None, CampaignContract);
+        let client = CampaignContractClient::new(&env, &contract_id);
+
+        let creator = Address::generate(&env);
+        let token = env
+            .register_stellar_asset_contract_v2(Address::generate(&env))
+            .address();
+        let campaign_id = BytesN::from_array(&env, &[0; 32]);
+
+        let campaign = client.initialize(
+            &creator,
+            &campaign_id,
+            &String::from_str(&env, "Test Campaign"),
+            &String::from_str(&env, "Test Description"),
+            &1000,
+            &Some(token.clone()),
+            &false,
+        );
+        assert_eq!(campaign.status, CampaignStatus::Draft);
 
         let donation_contract = Address::generate(&env);
         let verification_contract = Address::generate(&env);
@@ -256,14 +1456,1130 @@ mod test {
         let active_campaign = client.activate(&creator, &campaign_id);
         assert_eq!(active_campaign.status, CampaignStatus::Active);
 
-        let updated = client.add_donation(&campaign_id, &600);
+        let updated = client.add_donation(&campaign_id, &token, &600);
         assert_eq!(updated.current_amount, 600);
         assert!(client.is_active(&campaign_id));
 
-        let funded = client.add_donation(&campaign_id, &500);
+        let other_token = Address::generate(&env);
+        assert_eq!(
+            client.try_add_donation(&campaign_id, &other_token, &500),
+            Err(Ok(CampaignError::TokenNotAccepted.into()))
+        );
+        let funded = client.add_donation(&campaign_id, &token, &500);
         assert_eq!(funded.status, CampaignStatus::Funded);
+        assert_eq!(client.balance(&campaign_id, &token), 1100);
+        assert_eq!(client.balance(&campaign_id, &other_token), 0);
 
         let completed = client.mark_milestone_completed(&campaign_id, &1100);
         assert_eq!(completed.status, CampaignStatus::Completed);
     }
+
+    #[test]
+    fn test_hard_cap() {
+        let s = setup(1000);
+        assert_eq!(
+            s.client
+                .try_set_hard_cap(&s.creator, &s.campaign_id, &Some(999)),
+            Err(Ok(CampaignError::InvalidHardCap.into()))
+        );
+        s.client
+            .set_hard_cap(&s.creator, &s.campaign_id, &Some(1200));
+        s.client.add_donation(&s.campaign_id, &s.token, &1100);
+        assert_eq!(
+            s.client.try_add_donation(&s.campaign_id, &s.token, &101),
+            Err(Ok(CampaignError::HardCapExceeded.into()))
+        );
+        s.client.add_donation(&s.campaign_id, &s.token, &100);
+        assert_eq!(s.client.get(&s.campaign_id).current_amount, 1200);
+    }
+
+    #[test]
+    fn test_active_campaigns_paged() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register_contract(None, CampaignContract);
+        let client = CampaignContractClient::new(&env, &contract_id);
+
+        let creator = Address::generate(&env);
+        let token = env
+            .register_stellar_asset_contract_v2(Address::generate(&env))
+            .address();
+        let ids = [
+            BytesN::from_array(&env, &[1; 32]),
+            BytesN::from_array(&env, &[2; 32]),
+            BytesN::from_array(&env, &[3; 32]),
+        ];
+        for id in ids.iter() {
+            client.initialize(
+                &creator,
+                id,
+                &String::from_str(&env, "Test Campaign"),
+                &String::from_str(&env, "Test Description"),
+                &1000,
+                &Some(token.clone()),
+                &false,
+            );
+        }
+        assert_eq!(client.active_campaigns_paged(&0, &10).len(), 0);
+
+        for id in ids.iter() {
+            client.activate(&creator, id);
+        }
+        client.cancel(&creator, &ids[1]);
+
+        assert_eq!(
+            client.active_campaigns_paged(&0, &10),
+            vec![&env, ids[0].clone(), ids[2].clone()]
+        );
+        assert_eq!(
+            client.active_campaigns_paged(&1, &1),
+            vec![&env, ids[2].clone()]
+        );
+        assert_eq!(client.active_campaigns_paged(&2, &10).len(), 0);
+        assert_eq!(client.active_campaigns_paged(&0, &u32::MAX).len(), 2);
+
+        client.cancel(&creator, &ids[0]);
+        assert_eq!(
+            client.active_campaigns_paged(&0, &10),
+            vec![&env, ids[2].clone()]
+        );
+        client.cancel(&creator, &ids[2]);
+        assert_eq!(client.active_campaigns_paged(&0, &10).len(), 0);
+    }
+
+    #[test]
+    fn test_attest_balance() {
+        let s = setup(1000);
+        let (env, client) = (&s.env, &s.client);
+        env.ledger().with_mut(|li| {
+            li.sequence_number = 42;
+            li.timestamp = 1_700_000_000;
+        });
+        client.add_donation(&s.campaign_id, &s.token, &600);
+        client.mark_milestone_completed(&s.campaign_id, &250);
+
+        let attestation = client.attest_balance(&s.campaign_id);
+        assert_eq!(attestation.current_amount, 600);
+        assert_eq!(attestation.released_amount, 250);
+        assert_eq!(attestation.ledger_seq, 42);
+        assert_eq!(attestation.timestamp, 1_700_000_000);
+
+        let mut expected = [0u8; 76];
+        expected[..32].copy_from_slice(&s.campaign_id.to_array());
+        expected[32..48].copy_from_slice(&600i128.to_be_bytes());
+        expected[48..64].copy_from_slice(&250i128.to_be_bytes());
+        expected[64..68].copy_from_slice(&42u32.to_be_bytes());
+        expected[68..].copy_from_slice(&1_700_000_000u64.to_be_bytes());
+        let hash: BytesN<32> = env
+            .crypto()
+            .sha256(&Bytes::from_array(env, &expected))
+            .into();
+        assert_eq!(attestation.hash, hash);
+    }
+
+    #[test]
+    fn test_projected_impact() {
+        let s = setup(1000);
+        assert_eq!(s.client.projected_impact(&s.campaign_id), 0);
+
+        s.client.add_donation(&s.campaign_id, &s.token, &175);
+        let campaign = s.client.set_impact(
+            &s.creator,
+            &s.campaign_id,
+            &50,
+            &String::from_str(&s.env, "well"),
+        );
+        assert_eq!(campaign.impact_unit_label, String::from_str(&s.env, "well"));
+        assert_eq!(s.client.projected_impact(&s.campaign_id), 3);
+
+        assert_eq!(
+            s.client.try_set_impact(
+                &s.creator,
+                &s.campaign_id,
+                &-1,
+                &String::from_str(&s.env, "well"),
+            ),
+            Err(Ok(CampaignError::InvalidImpactCost.into()))
+        );
+    }
+
+    #[test]
+    fn test_emergency_shutdown() {
+        let s = setup(1000);
+        let admin = Address::generate(&s.env);
+        assert_eq!(
+            s.client.try_emergency_shutdown(&admin, &s.campaign_id),
+            Err(Ok(CampaignError::AdminNotSet.into()))
+        );
+
+        s.init_admin(&admin);
+        assert_eq!(
+            s.client.try_emergency_shutdown(&s.creator, &s.campaign_id),
+            Err(Ok(CampaignError::Unauthorized.into()))
+        );
+
+        s.client.add_donation(&s.campaign_id, &s.token, &300);
+        let campaign = s.client.emergency_shutdown(&admin, &s.campaign_id);
+        assert_eq!(campaign.status, CampaignStatus::Cancelled);
+        assert!(!s.client.is_active(&s.campaign_id));
+        assert_eq!(s.client.active_campaigns_paged(&0, &10).len(), 0);
+        assert_eq!(
+            s.env.events().all().last().unwrap().1,
+            (symbol_short!("shutdown"), s.campaign_id.clone()).into_val(&s.env)
+        );
+        assert_eq!(
+            s.client.try_add_donation(&s.campaign_id, &s.token, &1),
+            Err(Ok(CampaignError::NotActive.into()))
+        );
+        assert_eq!(
+            s.client.try_emergency_shutdown(&admin, &s.campaign_id),
+            Err(Ok(CampaignError::NotActive.into()))
+        );
+    }
+
+    #[test]
+    fn test_admin_bootstrap() {
+        let s = setup(1000);
+        let admin = Address::generate(&s.env);
+        let salt = BytesN::from_array(&s.env, &[0; 32]);
+        assert_eq!(
+            s.client.try_set_admin(&admin),
+            Err(Ok(CampaignError::AdminNotSet.into()))
+        );
+        assert_eq!(
+            s.client
+                .try_init_admin(&Address::generate(&s.env), &salt, &admin),
+            Err(Ok(CampaignError::Unauthorized.into()))
+        );
+
+        s.client.init_admin(&s.deployer, &salt, &admin);
+        assert_eq!(
+            s.client.try_init_admin(&s.deployer, &salt, &s.creator),
+            Err(Ok(CampaignError::Unauthorized.into()))
+        );
+        s.client.set_admin(&s.creator);
+        s.client.set_min_active_secs(&s.creator, &10);
+        assert_eq!(
+            s.client.try_set_min_active_secs(&admin, &10),
+            Err(Ok(CampaignError::Unauthorized.into()))
+        );
+    }
+
+    #[test]
+    fn test_format_amount() {
+        let s = setup(1000);
+        let format = |raw: i128| s.client.format_amount(&s.campaign_id, &raw);
+        assert_eq!(s.client.get(&s.campaign_id).decimals, 7);
+        assert_eq!(format(123_400_000), String::from_str(&s.env, "12.34"));
+        assert_eq!(format(10_000_000), String::from_str(&s.env, "1"));
+        assert_eq!(format(5), String::from_str(&s.env, "0.0000005"));
+        assert_eq!(format(0), String::from_str(&s.env, "0"));
+        assert_eq!(format(-15_000_000), String::from_str(&s.env, "-1.5"));
+        assert_eq!(
+            format(i128::MIN),
+            String::from_str(&s.env, "-17014118346046923173168730371588.4105728")
+        );
+    }
+
+    #[test]
+    fn test_add_donation_requires_configured_contracts() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register_contract(None, CampaignContract);
+        let client = CampaignContractClient::new(&env, &contract_id);
+
+        let creator = Address::generate(&env);
+        let token = env
+            .register_stellar_asset_contract_v2(Address::generate(&env))
+            .address();
+        let campaign_id = BytesN::from_array(&env, &[0; 32]);
+        client.initialize(
+            &creator,
+            &campaign_id,
+            &String::from_str(&env, "Test Campaign"),
+            &String::from_str(&env, "Test Description"),
+            &1000,
+            &Some(token.clone()),
+            &false,
+        );
+        client.activate(&creator, &campaign_id);
+
+        assert_eq!(client.donation_contract(&campaign_id), None);
+        assert_eq!(
+            client.try_add_donation(&campaign_id, &token, &100),
+            Err(Ok(CampaignError::ContractsNotConfigured.into()))
+        );
+        assert_eq!(
+            client.try_mark_milestone_completed(&campaign_id, &0),
+            Err(Ok(CampaignError::ContractsNotConfigured.into()))
+        );
+
+        let other = Address::generate(&env);
+        assert_eq!(
+            client.try_set_authorized_contracts(&other, &campaign_id, &Some(other.clone()), &None),
+            Err(Ok(CampaignError::Unauthorized.into()))
+        );
+    }
+
+    #[test]
+    fn test_add_donation_rejects_other_callers() {
+        let s = setup(1000);
+        let donation_contract = s.client.donation_contract(&s.campaign_id).unwrap();
+        let random = Address::generate(&s.env);
+        let invoke = MockAuthInvoke {
+            contract: &s.client.address,
+            fn_name: "add_donation",
+            args: (s.campaign_id.clone(), s.token.clone(), 100i128).into_val(&s.env),
+            sub_invokes: &[],
+        };
+        assert!(s
+            .client
+            .mock_auths(&[MockAuth {
+                address: &random,
+                invoke: &invoke,
+            }])
+            .try_add_donation(&s.campaign_id, &s.token, &100)
+            .is_err());
+        assert_eq!(s.client.get(&s.campaign_id).current_amount, 0);
+
+        let campaign = s
+            .client
+            .mock_auths(&[MockAuth {
+                address: &donation_contract,
+                invoke: &invoke,
+            }])
+            .add_donation(&s.campaign_id, &s.token, &100);
+        assert_eq!(campaign.current_amount, 100);
+    }
+
+    #[test]
+    fn test_mark_milestone_completed_rejects_other_callers() {
+        let s = setup(1000);
+        s.client.add_donation(&s.campaign_id, &s.token, &1000);
+        let verification_contract = s.client.verification_contract(&s.campaign_id).unwrap();
+        let random = Address::generate(&s.env);
+        let invoke = MockAuthInvoke {
+            contract: &s.client.address,
+            fn_name: "mark_milestone_completed",
+            args: (s.campaign_id.clone(), 1000i128).into_val(&s.env),
+            sub_invokes: &[],
+        };
+
+        assert!(s
+            .client
+            .mock_auths(&[MockAuth {
+                address: &random,
+                invoke: &invoke,
+            }])
+            .try_mark_milestone_completed(&s.campaign_id, &1000)
+            .is_err());
+        let campaign = s.client.get(&s.campaign_id);
+        assert_eq!(campaign.released_amount, 0);
+        assert_eq!(campaign.status, CampaignStatus::Funded);
+
+        let campaign = s
+            .client
+            .mock_auths(&[MockAuth {
+                address: &verification_contract,
+                invoke: &invoke,
+            }])
+            .mark_milestone_completed(&s.campaign_id, &1000);
+        assert_eq!(campaign.status, CampaignStatus::Completed);
+    }
+
+    #[test]
+    fn test_two_step_ownership_transfer() {
+        let s = setup(1000);
+        let new_owner = Address::generate(&s.env);
+        let stranger = Address::generate(&s.env);
+        assert_eq!(
+            s.client.try_accept_ownership(&new_owner, &s.campaign_id),
+            Err(Ok(CampaignError::NoPendingTransfer.into()))
+        );
+
+        let campaign = s
+            .client
+            .propose_ownership_transfer(&s.creator, &s.campaign_id, &new_owner);
+        assert_eq!(campaign.pending_owner, Some(new_owner.clone()));
+        assert_eq!(campaign.creator, s.creator);
+
+        assert_eq!(
+            s.client.try_accept_ownership(&stranger, &s.campaign_id),
+            Err(Ok(CampaignError::Unauthorized.into()))
+        );
+        let campaign = s.client.accept_ownership(&new_owner, &s.campaign_id);
+        assert_eq!(campaign.creator, new_owner);
+        assert_eq!(campaign.pending_owner, None);
+
+        assert_eq!(
+            s.client.try_cancel(&s.creator, &s.campaign_id),
+            Err(Ok(CampaignError::Unauthorized.into()))
+        );
+    }
+
+    #[test]
+    fn test_get_many() {
+        let s = setup(1000);
+        let missing = BytesN::from_array(&s.env, &[8; 32]);
+        let campaigns = s.client.get_many(&vec![
+            &s.env,
+            missing.clone(),
+            s.campaign_id.clone(),
+            missing.clone(),
+        ]);
+        assert_eq!(
+            campaigns,
+            vec![&s.env, None, Some(s.client.get(&s.campaign_id)), None]
+        );
+
+        let mut too_many = vec![&s.env];
+        for _ in 0..=MAX_BATCH_SIZE {
+            too_many.push_back(missing.clone());
+        }
+        assert_eq!(
+            s.client.try_get_many(&too_many),
+            Err(Ok(CampaignError::BatchTooLarge.into()))
+        );
+    }
+
+    #[test]
+    fn test_creator_pause() {
+        let s = setup(1000);
+        s.client.pause(&s.creator, &s.campaign_id);
+        assert!(!s.client.is_active(&s.campaign_id));
+        assert_eq!(s.client.status(&s.campaign_id), CampaignStatus::Active);
+        assert_eq!(
+            s.client.try_add_donation(&s.campaign_id, &s.token, &100),
+            Err(Ok(CampaignError::Paused.into()))
+        );
+        assert_eq!(
+            s.client
+                .try_pause(&Address::generate(&s.env), &s.campaign_id),
+            Err(Ok(CampaignError::Unauthorized.into()))
+        );
+
+        s.client.unpause(&s.creator, &s.campaign_id);
+        assert!(s.client.is_active(&s.campaign_id));
+        let campaign = s.client.add_donation(&s.campaign_id, &s.token, &100);
+        assert_eq!(campaign.current_amount, 100);
+    }
+
+    #[test]
+    fn test_target_band() {
+        let s = setup(1000);
+        let admin = Address::generate(&s.env);
+        s.init_admin(&admin);
+        s.client
+            .set_target_band(&admin, &s.token, &Some((500, 5_000)));
+        assert_eq!(s.client.target_band(&s.token), Some((500, 5_000)));
+
+        let title = String::from_str(&s.env, "Banded");
+        assert_eq!(
+            s.client.try_initialize(
+                &s.creator,
+                &BytesN::from_array(&s.env, &[8; 32]),
+                &title,
+                &title,
+                &10_000,
+                &Some(s.token.clone()),
+                &false
+            ),
+            Err(Ok(CampaignError::TargetOutOfBand.into()))
+        );
+        s.client.initialize(
+            &s.creator,
+            &BytesN::from_array(&s.env, &[8; 32]),
+            &title,
+            &title,
+            &2_000,
+            &Some(s.token.clone()),
+            &false,
+        );
+
+        s.client.set_target_band(&admin, &s.token, &None);
+        s.client.initialize(
+            &s.creator,
+            &BytesN::from_array(&s.env, &[9; 32]),
+            &title,
+            &title,
+            &10_000,
+            &Some(s.token.clone()),
+            &false,
+        );
+    }
+
+    #[test]
+    fn test_invariants_hold_through_lifecycle() {
+        let s = setup(1000);
+        let holds = Invariants {
+            amounts_non_negative: true,
+            released_within_raised: true,
+            within_hard_cap: true,
+            balances_match: true,
+            status_consistent: true,
+        };
+        assert_eq!(s.client.invariants(&s.campaign_id), holds);
+
+        s.client
+            .set_hard_cap(&s.creator, &s.campaign_id, &Some(1500));
+        s.client.add_donation(&s.campaign_id, &s.token, &700);
+        assert_eq!(s.client.invariants(&s.campaign_id), holds);
+        assert_eq!(
+            s.client.try_add_donation(&s.campaign_id, &s.token, &900),
+            Err(Ok(CampaignError::HardCapExceeded.into()))
+        );
+        s.client.add_donation(&s.campaign_id, &s.token, &800);
+        assert_eq!(s.client.invariants(&s.campaign_id), holds);
+
+        assert_eq!(
+            s.client.try_mark_milestone_completed(&s.campaign_id, &1600),
+            Err(Ok(CampaignError::InsufficientFunds.into()))
+        );
+        s.client.mark_milestone_completed(&s.campaign_id, &400);
+        assert_eq!(s.client.invariants(&s.campaign_id), holds);
+        s.client.mark_milestone_completed(&s.campaign_id, &1100);
+        assert_eq!(s.client.status(&s.campaign_id), CampaignStatus::Completed);
+        assert_eq!(s.client.invariants(&s.campaign_id), holds);
+    }
+
+    #[test]
+    fn test_auto_activate_on_first_donation() {
+        let s = setup(1000);
+        let donation = s.client.get(&s.campaign_id).donation_contract.unwrap();
+        let title = String::from_str(&s.env, "Self-funded");
+
+        let auto_id = BytesN::from_array(&s.env, &[8; 32]);
+        s.client.initialize(
+            &s.creator,
+            &auto_id,
+            &title,
+            &title,
+            &1000,
+            &Some(s.token.clone()),
+            &true,
+        );
+        s.client
+            .set_authorized_contracts(&s.creator, &auto_id, &Some(donation.clone()), &None);
+        let campaign = s.client.add_donation(&auto_id, &s.token, &100);
+        assert_eq!(campaign.status, CampaignStatus::Active);
+        assert_eq!(campaign.current_amount, 100);
+        assert_eq!(
+            s.client.active_campaigns_paged(&0, &10),
+            vec![&s.env, s.campaign_id.clone(), auto_id]
+        );
+
+        let manual_id = BytesN::from_array(&s.env, &[9; 32]);
+        s.client.initialize(
+            &s.creator,
+            &manual_id,
+            &title,
+            &title,
+            &1000,
+            &Some(s.token.clone()),
+            &false,
+        );
+        s.client
+            .set_authorized_contracts(&s.creator, &manual_id, &Some(donation), &None);
+        assert_eq!(
+            s.client.try_add_donation(&manual_id, &s.token, &100),
+            Err(Ok(CampaignError::NotActive.into()))
+        );
+    }
+
+    #[test]
+    fn test_reap_stale() {
+        let s = setup(1000);
+        let admin = Address::generate(&s.env);
+        s.init_admin(&admin);
+        s.client.set_stale_window(&admin, &1_000);
+
+        s.env.ledger().with_mut(|li| li.timestamp = 500);
+        s.client.add_donation(&s.campaign_id, &s.token, &100);
+        s.env.ledger().with_mut(|li| li.timestamp = 1_200);
+        assert_eq!(
+            s.client.try_reap_stale(&s.campaign_id),
+            Err(Ok(CampaignError::NotStale.into()))
+        );
+
+        s.env.ledger().with_mut(|li| li.timestamp = 1_500);
+        let campaign = s.client.reap_stale(&s.campaign_id);
+        assert_eq!(campaign.status, CampaignStatus::Expired);
+        assert!(!s.client.is_active(&s.campaign_id));
+        assert_eq!(s.client.active_campaigns_paged(&0, &10).len(), 0);
+        assert_eq!(
+            s.env.events().all().last().unwrap().1,
+            (symbol_short!("expired"), s.campaign_id.clone()).into_val(&s.env)
+        );
+        assert_eq!(
+            s.client.try_add_donation(&s.campaign_id, &s.token, &100),
+            Err(Ok(CampaignError::NotActive.into()))
+        );
+    }
+
+    #[test]
+    fn test_describe_error() {
+        let s = setup(1000);
+        assert_eq!(
+            s.client
+                .describe_error(&(CampaignError::Unauthorized as u32)),
+            symbol_short!("unauth")
+        );
+        for code in 1..=26 {
+            assert_ne!(s.client.describe_error(&code), symbol_short!("unknown"));
+        }
+        assert_eq!(s.client.describe_error(&27), symbol_short!("unknown"));
+        assert_eq!(s.client.describe_error(&0), symbol_short!("unknown"));
+    }
+
+    #[test]
+    fn test_unlisted_campaigns_are_not_indexed() {
+        let s = setup(1000);
+        assert_eq!(s.client.active_campaigns_paged(&0, &10).len(), 1);
+
+        s.client
+            .set_visibility(&s.creator, &s.campaign_id, &Visibility::Unlisted);
+        assert_eq!(s.client.active_campaigns_paged(&0, &10).len(), 0);
+        assert!(s.client.is_active(&s.campaign_id));
+        s.client.add_donation(&s.campaign_id, &s.token, &1000);
+        assert_eq!(s.client.status(&s.campaign_id), CampaignStatus::Funded);
+        assert_eq!(s.client.active_campaigns_paged(&0, &10).len(), 0);
+
+        s.client
+            .set_visibility(&s.creator, &s.campaign_id, &Visibility::Public);
+        assert_eq!(
+            s.client.active_campaigns_paged(&0, &10),
+            vec![&s.env, s.campaign_id.clone()]
+        );
+    }
+
+    #[test]
+    fn test_endowment_split() {
+        let s = setup(1000);
+        let endowment = Address::generate(&s.env);
+        assert_eq!(
+            s.client.try_set_endowment(
+                &s.creator,
+                &s.campaign_id,
+                &Some(endowment.clone()),
+                &2_000
+            ),
+            Err(Ok(CampaignError::NotDraft.into()))
+        );
+
+        let campaign_id = BytesN::from_array(&s.env, &[9; 32]);
+        let title = String::from_str(&s.env, "Endowed");
+        s.client.initialize(
+            &s.creator,
+            &campaign_id,
+            &title,
+            &title,
+            &1000,
+            &Some(s.token.clone()),
+            &false,
+        );
+        s.client.set_authorized_contracts(
+            &s.creator,
+            &campaign_id,
+            &Some(Address::generate(&s.env)),
+            &None,
+        );
+        assert_eq!(
+            s.client
+                .try_set_endowment(&s.creator, &campaign_id, &None, &1_000),
+            Err(Ok(CampaignError::InvalidEndowment.into()))
+        );
+        assert_eq!(
+            s.client
+                .try_set_endowment(&s.creator, &campaign_id, &Some(endowment.clone()), &10_001),
+            Err(Ok(CampaignError::InvalidEndowment.into()))
+        );
+        s.client
+            .set_endowment(&s.creator, &campaign_id, &Some(endowment), &2_000);
+        s.client.activate(&s.creator, &campaign_id);
+
+        let campaign = s.client.add_donation(&campaign_id, &s.token, &1000);
+        assert_eq!(campaign.current_amount, 800);
+        assert_eq!(campaign.endowed_amount, 200);
+        assert_eq!(campaign.status, CampaignStatus::Active);
+        assert_eq!(s.client.balance(&campaign_id, &s.token), 800);
+
+        let campaign = s.client.add_donation(&campaign_id, &s.token, &250);
+        assert_eq!(campaign.current_amount, 1000);
+        assert_eq!(campaign.endowed_amount, 250);
+        assert_eq!(campaign.status, CampaignStatus::Funded);
+
+        assert_eq!(endowment_share(i128::MAX, MAX_BPS), i128::MAX);
+        assert_eq!(endowment_share(i128::MAX, 5_000), i128::MAX / 2);
+        assert_eq!(endowment_share(1_999, 5_000), 999);
+    }
+
+    #[test]
+    fn test_status_history() {
+        let s = setup(1000);
+        s.env.ledger().with_mut(|li| li.timestamp = 100);
+        s.client.add_donation(&s.campaign_id, &s.token, &1000);
+        s.env.ledger().with_mut(|li| li.timestamp = 200);
+        s.client.cancel(&s.creator, &s.campaign_id);
+        s.client.cancel(&s.creator, &s.campaign_id);
+
+        assert_eq!(
+            s.client.status_history(&s.campaign_id),
+            vec![
+                &s.env,
+                (CampaignStatus::Draft, 0),
+                (CampaignStatus::Active, 0),
+                (CampaignStatus::Funded, 100),
+                (CampaignStatus::Cancelled, 200),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_funds_released_callback() {
+        let s = setup(1000);
+        s.client.add_donation(&s.campaign_id, &s.token, &1000);
+
+        let listener = s.env.register_contract(None, Listener);
+        let listener_client = ListenerClient::new(&s.env, &listener);
+        s.client
+            .set_notify_contract(&s.creator, &s.campaign_id, &Some(listener));
+        s.client.mark_milestone_completed(&s.campaign_id, &300);
+        assert_eq!(listener_client.last(), Some((s.campaign_id.clone(), 300)));
+
+        listener_client.break_listener();
+        let campaign = s.client.mark_milestone_completed(&s.campaign_id, &200);
+        assert_eq!(campaign.released_amount, 500);
+        assert_eq!(listener_client.last(), Some((s.campaign_id.clone(), 300)));
+    }
+
+    #[test]
+    fn test_min_active_secs_before_cancel() {
+        let s = setup(1000);
+        let admin = Address::generate(&s.env);
+        s.init_admin(&admin);
+        s.client.set_min_active_secs(&admin, &1_000);
+
+        let title = String::from_str(&s.env, "Unfunded");
+        let unfunded_id = BytesN::from_array(&s.env, &[8; 32]);
+        s.client.initialize(
+            &s.creator,
+            &unfunded_id,
+            &title,
+            &title,
+            &1000,
+            &Some(s.token.clone()),
+            &false,
+        );
+        s.client.activate(&s.creator, &unfunded_id);
+        s.client.cancel(&s.creator, &unfunded_id);
+
+        s.client.add_donation(&s.campaign_id, &s.token, &100);
+        s.env.ledger().with_mut(|li| li.timestamp = 999);
+        assert_eq!(
+            s.client.try_cancel(&s.creator, &s.campaign_id),
+            Err(Ok(CampaignError::TooEarlyToCancel.into()))
+        );
+        s.env.ledger().with_mut(|li| li.timestamp = 1_000);
+        let campaign = s.client.cancel(&s.creator, &s.campaign_id);
+        assert_eq!(campaign.status, CampaignStatus::Cancelled);
+
+        s.client.set_min_active_secs(&admin, &u64::MAX);
+        let locked_id = BytesN::from_array(&s.env, &[9; 32]);
+        s.client.initialize(
+            &s.creator,
+            &locked_id,
+            &title,
+            &title,
+            &1000,
+            &Some(s.token.clone()),
+            &false,
+        );
+        s.client.set_authorized_contracts(
+            &s.creator,
+            &locked_id,
+            &Some(Address::generate(&s.env)),
+            &None,
+        );
+        s.client.activate(&s.creator, &locked_id);
+        s.client.add_donation(&locked_id, &s.token, &100);
+        s.env.ledger().with_mut(|li| li.timestamp = u64::MAX - 1);
+        assert_eq!(
+            s.client.try_cancel(&s.creator, &locked_id),
+            Err(Ok(CampaignError::TooEarlyToCancel.into()))
+        );
+    }
+
+    #[test]
+    fn test_export_import() {
+        let s = setup(1000);
+        s.client.add_donation(&s.campaign_id, &s.token, &400);
+        let export = s.client.export(&s.campaign_id);
+
+        let admin = Address::generate(&s.env);
+        s.init_admin(&admin);
+        assert_eq!(
+            s.client.try_import(&admin, &export),
+            Err(Ok(CampaignError::CampaignExists.into()))
+        );
+
+        let fresh = CampaignContractClient::new(
+            &s.env,
+            &s.env.register_contract(
+                Some(&test_deployed_address(&s.env, &s.deployer, 1)),
+                CampaignContract,
+            ),
+        );
+        fresh.init_admin(&s.deployer, &test_salt(&s.env, 1), &admin);
+        let mut tampered = export.clone();
+        tampered.campaign.current_amount = 4_000;
+        assert_eq!(
+            fresh.try_import(&admin, &tampered),
+            Err(Ok(CampaignError::InvalidExport.into()))
+        );
+
+        let mut tampered = export.clone();
+        tampered.vested_tranches = 1;
+        assert_eq!(
+            fresh.try_import(&admin, &tampered),
+            Err(Ok(CampaignError::InvalidExport.into()))
+        );
+        assert!(export.reference_hashes.is_empty());
+
+        fresh.set_token_denied(&admin, &s.token, &true);
+        assert_eq!(
+            fresh.try_import(&admin, &export),
+            Err(Ok(CampaignError::TokenDenied.into()))
+        );
+        fresh.set_token_denied(&admin, &s.token, &false);
+        fresh.set_target_band(&admin, &s.token, &Some((2_000, 5_000)));
+        assert_eq!(
+            fresh.try_import(&admin, &export),
+            Err(Ok(CampaignError::TargetOutOfBand.into()))
+        );
+        fresh.set_target_band(&admin, &s.token, &None);
+
+        let imported = fresh.import(&admin, &export);
+        assert_eq!(imported, s.client.get(&s.campaign_id));
+        assert_eq!(
+            fresh.status_history(&s.campaign_id),
+            s.client.status_history(&s.campaign_id)
+        );
+        assert_eq!(
+            fresh.active_campaigns_paged(&0, &10),
+            vec![&s.env, s.campaign_id.clone()]
+        );
+
+        let vested_id = BytesN::from_array(&s.env, &[9; 32]);
+        let title = String::from_str(&s.env, "Vested");
+        s.client.initialize(
+            &s.creator,
+            &vested_id,
+            &title,
+            &title,
+            &1000,
+            &Some(s.token.clone()),
+            &false,
+        );
+        let schedule = vec![&s.env, (100, 400), (200, 600)];
+        s.client
+            .set_vesting_schedule(&s.creator, &vested_id, &schedule);
+        fresh.import(&admin, &s.client.export(&vested_id));
+        assert_eq!(fresh.vesting_schedule(&vested_id), schedule);
+        assert_eq!(fresh.vested_tranches(&vested_id), 0);
+    }
+
+    #[test]
+    fn test_funding_velocity() {
+        let s = setup(1000);
+        assert_eq!(s.client.funding_velocity(&s.campaign_id), 0);
+        assert_eq!(s.client.projected_completion(&s.campaign_id), None);
+
+        s.client.add_donation(&s.campaign_id, &s.token, &250);
+        assert_eq!(s.client.funding_velocity(&s.campaign_id), 0);
+
+        s.env
+            .ledger()
+            .with_mut(|li| li.timestamp += 2 * SECS_PER_DAY);
+        assert_eq!(s.client.funding_velocity(&s.campaign_id), 125);
+        let now = s.env.ledger().timestamp();
+        assert_eq!(
+            s.client.projected_completion(&s.campaign_id),
+            Some(now + 6 * SECS_PER_DAY)
+        );
+
+        s.client.add_donation(&s.campaign_id, &s.token, &750);
+        assert_eq!(s.client.projected_completion(&s.campaign_id), Some(now));
+
+        // A projection that overflows is reported as unknown rather than trapping.
+        let huge = setup(i128::MAX);
+        huge.client.add_donation(&huge.campaign_id, &huge.token, &1);
+        huge.env
+            .ledger()
+            .with_mut(|li| li.timestamp += 2 * SECS_PER_DAY);
+        assert_eq!(huge.client.projected_completion(&huge.campaign_id), None);
+
+        let raised = i128::MAX / 2;
+        huge.client
+            .add_donation(&huge.campaign_id, &huge.token, &raised);
+        assert_eq!(
+            huge.client.funding_velocity(&huge.campaign_id),
+            (raised + 1) / (2 * SECS_PER_DAY as i128) * SECS_PER_DAY as i128
+        );
+    }
+
+    #[test]
+    fn test_snapshots() {
+        let s = setup(1000);
+        assert_eq!(
+            s.client
+                .try_snapshot(&Address::generate(&s.env), &s.campaign_id),
+            Err(Ok(CampaignError::Unauthorized.into()))
+        );
+
+        s.env.ledger().with_mut(|li| li.sequence_number = 10);
+        s.client.snapshot(&s.creator, &s.campaign_id);
+        let at_ten = s.client.get(&s.campaign_id);
+
+        s.env.ledger().with_mut(|li| li.sequence_number = 20);
+        s.client.add_donation(&s.campaign_id, &s.token, &300);
+        s.client.snapshot(&s.creator, &s.campaign_id);
+
+        assert_eq!(s.client.get_campaign_at(&s.campaign_id, &5), None);
+        assert_eq!(s.client.get_campaign_at(&s.campaign_id, &15), Some(at_ten));
+        assert_eq!(
+            s.client
+                .get_campaign_at(&s.campaign_id, &25)
+                .map(|campaign| campaign.current_amount),
+            Some(300)
+        );
+
+        for seq in 21..21 + MAX_SNAPSHOTS {
+            s.env.ledger().with_mut(|li| li.sequence_number = seq);
+            s.client.snapshot(&s.creator, &s.campaign_id);
+        }
+        assert_eq!(s.client.get_campaign_at(&s.campaign_id, &15), None);
+        assert!(s.client.get_campaign_at(&s.campaign_id, &21).is_some());
+    }
+
+    #[test]
+    fn test_goal_reached() {
+        let s = setup(1000);
+        s.client.add_donation(&s.campaign_id, &s.token, &600);
+        assert!(!s.client.is_goal_reached(&s.campaign_id));
+        assert_eq!(s.client.get(&s.campaign_id).goal_reached_at, None);
+
+        s.env.ledger().with_mut(|li| li.timestamp = 1_000);
+        s.client.add_donation(&s.campaign_id, &s.token, &400);
+        assert!(s.client.is_goal_reached(&s.campaign_id));
+        assert_eq!(s.client.get(&s.campaign_id).goal_reached_at, Some(1_000));
+
+        s.env.ledger().with_mut(|li| li.timestamp = 2_000);
+        s.client.add_donation(&s.campaign_id, &s.token, &100);
+        assert_eq!(s.client.get(&s.campaign_id).goal_reached_at, Some(1_000));
+    }
+
+    #[test]
+    fn test_vesting_schedule() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let client =
+            CampaignContractClient::new(&env, &env.register_contract(None, CampaignContract));
+        let creator = Address::generate(&env);
+        let token = env
+            .register_stellar_asset_contract_v2(Address::generate(&env))
+            .address();
+        let campaign_id = BytesN::from_array(&env, &[3; 32]);
+        client.initialize(
+            &creator,
+            &campaign_id,
+            &String::from_str(&env, "Vested"),
+            &String::from_str(&env, "Time-locked release"),
+            &1000,
+            &Some(token.clone()),
+            &false,
+        );
+
+        assert_eq!(
+            client.try_set_vesting_schedule(
+                &creator,
+                &campaign_id,
+                &vec![&env, (100, 400), (200, 500)]
+            ),
+            Err(Ok(CampaignError::InvalidSchedule.into()))
+        );
+        assert_eq!(
+            client.try_set_vesting_schedule(
+                &creator,
+                &campaign_id,
+                &vec![&env, (200, 400), (100, 600)]
+            ),
+            Err(Ok(CampaignError::InvalidSchedule.into()))
+        );
+        let schedule = vec![&env, (100, 400), (200, 300), (300, 300)];
+        client.set_vesting_schedule(&creator, &campaign_id, &schedule);
+        assert_eq!(client.vesting_schedule(&campaign_id), schedule);
+
+        assert_eq!(
+            client.try_release_vested(&creator, &campaign_id),
+            Err(Ok(CampaignError::NotActive.into()))
+        );
+
+        client.set_authorized_contracts(
+            &creator,
+            &campaign_id,
+            &Some(Address::generate(&env)),
+            &Some(Address::generate(&env)),
+        );
+        client.activate(&creator, &campaign_id);
+        client.add_donation(&campaign_id, &token, &1000);
+        assert_eq!(
+            client.try_mark_milestone_completed(&campaign_id, &100),
+            Err(Ok(CampaignError::VestingEnabled.into()))
+        );
+
+        assert_eq!(client.release_vested(&creator, &campaign_id), 0);
+        env.ledger().with_mut(|li| li.timestamp = 250);
+        assert_eq!(client.release_vested(&creator, &campaign_id), 700);
+        assert_eq!(client.vested_tranches(&campaign_id), 2);
+        assert_eq!(client.release_vested(&creator, &campaign_id), 0);
+
+        env.ledger().with_mut(|li| li.timestamp = 300);
+        assert_eq!(client.release_vested(&creator, &campaign_id), 300);
+        let campaign = client.get(&campaign_id);
+        assert_eq!(campaign.released_amount, 1000);
+        assert_eq!(campaign.status, CampaignStatus::Completed);
+        assert_eq!(
+            client.try_release_vested(&creator, &campaign_id),
+            Err(Ok(CampaignError::NotActive.into()))
+        );
+    }
+
+    #[test]
+    fn test_token_denylist() {
+        let s = setup(1000);
+        let admin = Address::generate(&s.env);
+        s.init_admin(&admin);
+        assert!(!s.client.is_token_denied(&s.token));
+
+        s.client.set_token_denied(&admin, &s.token, &true);
+        s.client.set_token_denied(&admin, &s.token, &true);
+        assert!(s.client.is_token_denied(&s.token));
+        assert_eq!(
+            s.client.try_initialize(
+                &s.creator,
+                &BytesN::from_array(&s.env, &[8; 32]),
+                &String::from_str(&s.env, "Scam"),
+                &String::from_str(&s.env, "Denied token"),
+                &1000,
+                &Some(s.token.clone()),
+                &false,
+            ),
+            Err(Ok(CampaignError::TokenDenied.into()))
+        );
+
+        s.client.set_token_denied(&admin, &s.token, &false);
+        assert!(!s.client.is_token_denied(&s.token));
+        s.client.initialize(
+            &s.creator,
+            &BytesN::from_array(&s.env, &[8; 32]),
+            &String::from_str(&s.env, "Allowed"),
+            &String::from_str(&s.env, "Token reinstated"),
+            &1000,
+            &Some(s.token.clone()),
+            &false,
+        );
+    }
+
+    #[test]
+    fn test_default_token() {
+        let s = setup(1000);
+        let admin = Address::generate(&s.env);
+        s.init_admin(&admin);
+        let id = BytesN::from_array(&s.env, &[8; 32]);
+        let title = String::from_str(&s.env, "Default");
+        assert_eq!(
+            s.client
+                .try_initialize(&s.creator, &id, &title, &title, &1000, &None, &false),
+            Err(Ok(CampaignError::NoDefaultToken.into()))
+        );
+
+        s.client.set_default_token(&admin, &s.token);
+        assert_eq!(s.client.default_token(), Some(s.token.clone()));
+        let campaign = s
+            .client
+            .initialize(&s.creator, &id, &title, &title, &1000, &None, &false);
+        assert_eq!(campaign.token, s.token);
+    }
+
+    #[test]
+    fn test_clone_campaign() {
+        let s = setup(1000);
+        s.client
+            .set_hard_cap(&s.creator, &s.campaign_id, &Some(1500));
+        s.client
+            .set_notify_contract(&s.creator, &s.campaign_id, &Some(Address::generate(&s.env)));
+        s.client.add_donation(&s.campaign_id, &s.token, &1000);
+        let new_id = BytesN::from_array(&s.env, &[9; 32]);
+
+        assert_eq!(
+            s.client
+                .try_clone_campaign(&Address::generate(&s.env), &s.campaign_id, &new_id),
+            Err(Ok(CampaignError::Unauthorized.into()))
+        );
+        assert_eq!(
+            s.client
+                .try_clone_campaign(&s.creator, &s.campaign_id, &s.campaign_id),
+            Err(Ok(CampaignError::CampaignExists.into()))
+        );
+
+        let admin = Address::generate(&s.env);
+        s.init_admin(&admin);
+        s.client
+            .set_target_band(&admin, &s.token, &Some((2_000, 5_000)));
+        assert_eq!(
+            s.client
+                .try_clone_campaign(&s.creator, &s.campaign_id, &new_id),
+            Err(Ok(CampaignError::TargetOutOfBand.into()))
+        );
+        s.client.set_target_band(&admin, &s.token, &None);
+
+        s.env.ledger().with_mut(|li| li.timestamp = 5_000);
+        let clone = s.client.clone_campaign(&s.creator, &s.campaign_id, &new_id);
+        let source = s.client.get(&s.campaign_id);
+        assert_eq!(clone.id, new_id);
+        assert_eq!(clone.title, source.title);
+        assert_eq!(clone.target_amount, source.target_amount);
+        assert_eq!(clone.hard_cap, Some(1500));
+        assert_eq!(clone.donation_contract, source.donation_contract);
+        assert_eq!(clone.notify_contract, None);
+        assert_eq!(clone.status, CampaignStatus::Draft);
+        assert_eq!(clone.current_amount, 0);
+        assert_eq!(clone.balances.len(), 0);
+        assert_eq!(clone.created_at, 5_000);
+        assert_eq!(clone.goal_reached_at, None);
+        assert_eq!(
+            s.client.status_history(&new_id),
+            vec![&s.env, (CampaignStatus::Draft, 5_000)]
+        );
+    }
+
+    #[test]
+    fn test_statuses() {
+        let s = setup(1000);
+        let missing = BytesN::from_array(&s.env, &[1; 32]);
+        assert_eq!(
+            s.client
+                .statuses(&vec![&s.env, s.campaign_id.clone(), missing.clone()]),
+            vec![&s.env, Some(CampaignStatus::Active), None]
+        );
+
+        let mut ids = vec![&s.env];
+        for _ in 0..=MAX_BATCH_SIZE {
+            ids.push_back(missing.clone());
+        }
+        assert_eq!(
+            s.client.try_statuses(&ids),
+            Err(Ok(CampaignError::BatchTooLarge.into()))
+        );
+    }
 }
@@ -1,9 +1,12 @@
 #![no_std]
-use givehub_campaign::CampaignContractClient;
+use givehub_admin::AdminError;
+use givehub_campaign::{endowment_share, Campaign, CampaignContractClient, CampaignStatus};
+use givehub_verification::{MilestoneStatus, VerificationContractClient};
 use soroban_sdk::{
     auth::{ContractContext, InvokerContractAuthEntry, SubContractInvocation},
-    contract, contracterror, contractimpl, contracttype, panic_with_error, vec, Address, BytesN,
-    Env, IntoVal, Map, String, Symbol, Vec,
+    contract, contracterror, contractimpl, contracttype, panic_with_error, symbol_short, vec,
+    xdr::ToXdr,
+    Address, Bytes, BytesN, Env, Error, IntoVal, Map, String, Symbol, Vec,
 };
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -11,9 +14,64 @@ use soroban_sdk::{
 pub struct Donation {
     pub campaign_id: BytesN<32>,
     pub donor: Address,
+    pub token: Address,
     pub amount: i128,
+    pub endowed: i128,
     pub timestamp: u64,
     pub note: Option<String>,
+    pub epoch: u32,
+    pub allocation: Vec<(u32, i128)>,
+    pub external: bool,
+    pub campaign_contract: Address,
+}
+
+/// Data of the `receipt` event published by `donate` under the topics
+/// `(symbol_short!("receipt"), campaign_id, donor)`. Receipt generators parse
+/// this directly, so the field order and types are a stable schema and must
+/// not change without a version bump. `reference` is the donation's index in
+/// `get_donations(campaign_id, donor)`, or its position in the donor's
+/// aggregate count for campaigns using `compact_donations`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct DonationReceipt {
+    pub amount: i128,
+    pub timestamp: u64,
+    pub reference: u32,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct PendingDonation {
+    pub donation: Donation,
+    pub confirm_after: u32,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct DonorAggregate {
+    pub total: i128,
+    pub count: u32,
+    pub last_timestamp: u64,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct TopDonor {
+    pub donor: Address,
+    pub total: i128,
+    pub display_name: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum DonateCheck {
+    Ok,
+    Unauthorized,
+    CampaignInactive,
+    HardCapReached,
+    InvalidAmount,
+    DonorNotAllowed,
+    TokenDenied,
 }
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
@@ -23,13 +81,67 @@ pub enum DonationError {
     CampaignInactive = 1,
     InvalidAmount = 2,
     Unauthorized = 3,
+    HardCapReached = 4,
+    AdminNotSet = 5,
+    NameTooLong = 6,
+    NoDonors = 7,
+    InvalidAllocation = 8,
+    DonationNotFound = 9,
+    MilestoneLocked = 10,
+    DonorNotAllowed = 11,
+    TokenDenied = 12,
+    TokenNotAccepted = 13,
+    PendingQueueFull = 14,
+}
+
+impl DonationError {
+    fn symbol(self) -> Symbol {
+        match self {
+            DonationError::CampaignInactive => symbol_short!("inactive"),
+            DonationError::InvalidAmount => symbol_short!("bad_amt"),
+            DonationError::Unauthorized => symbol_short!("unauth"),
+            DonationError::HardCapReached => symbol_short!("cap_hit"),
+            DonationError::AdminNotSet => symbol_short!("no_admin"),
+            DonationError::NameTooLong => symbol_short!("long_name"),
+            DonationError::NoDonors => symbol_short!("no_donors"),
+            DonationError::InvalidAllocation => symbol_short!("bad_alloc"),
+            DonationError::DonationNotFound => symbol_short!("no_dontn"),
+            DonationError::MilestoneLocked => symbol_short!("ms_locked"),
+            DonationError::DonorNotAllowed => symbol_short!("not_allow"),
+            DonationError::TokenDenied => symbol_short!("denied"),
+            DonationError::TokenNotAccepted => symbol_short!("bad_token"),
+            DonationError::PendingQueueFull => symbol_short!("q_full"),
+        }
+    }
+}
+
+impl From<AdminError> for DonationError {
+    fn from(error: AdminError) -> Self {
+        match error {
+            AdminError::Unauthorized => DonationError::Unauthorized,
+            AdminError::AdminNotSet => DonationError::AdminNotSet,
+        }
+    }
 }
 
+pub const MAX_TOP_DONORS: u32 = 50;
+pub const MAX_DISPLAY_NAME_LEN: u32 = 64;
+pub const MAX_PENDING_DONATIONS: u32 = 50;
+
 #[contract]
 pub struct DonationContract;
 
 #[contractimpl]
 impl DonationContract {
+    pub fn init_admin(env: Env, deployer: Address, salt: BytesN<32>, admin: Address) {
+        givehub_admin::init_admin::<DonationError>(&env, deployer, salt, admin);
+    }
+
+    pub fn set_admin(env: Env, admin: Address) {
+        givehub_admin::set_admin::<DonationError>(&env, admin);
+    }
+
+    #[allow(clippy::too_many_arguments)]
     pub fn donate(
         env: Env,
         donor: Address,
@@ -37,133 +149,2153 @@ impl DonationContract {
         campaign_id: BytesN<32>,
         amount: i128,
         note: Option<String>,
+        token: Option<Address>,
+        allocation: Option<Vec<(u32, i128)>>,
     ) -> Donation {
         donor.require_auth();
 
         if amount <= 0 {
             panic_with_error!(&env, DonationError::InvalidAmount);
         }
+        let allocation = allocation.unwrap_or_else(|| vec![&env]);
+        Self::check_allocation(&env, &allocation, amount);
 
         let campaign_client = CampaignContractClient::new(&env, &campaign_contract);
         let campaign = campaign_client.get(&campaign_id);
-        if campaign.donation_contract != Some(env.current_contract_address()) {
-            panic_with_error!(&env, DonationError::Unauthorized);
-        }
-        if !matches!(
-            campaign.status,
-            givehub_campaign::CampaignStatus::Active | givehub_campaign::CampaignStatus::Funded
+        Self::require_donatable(&env, &campaign, amount);
+        Self::check_milestones_pending(&env, &campaign, allocation.iter().map(|(index, _)| index));
+        if !Self::is_donor_allowed(
+            env.clone(),
+            campaign_contract.clone(),
+            campaign_id.clone(),
+            donor.clone(),
         ) {
-            panic_with_error!(&env, DonationError::CampaignInactive);
+            panic_with_error!(&env, DonationError::DonorNotAllowed);
+        }
+        let token = token.unwrap_or_else(|| campaign.token.clone());
+        if campaign_client.is_token_denied(&token) {
+            panic_with_error!(&env, DonationError::TokenDenied);
+        }
+        if token != campaign.token {
+            panic_with_error!(&env, DonationError::TokenNotAccepted);
         }
 
-        let auth_entry = InvokerContractAuthEntry::Contract(SubContractInvocation {
-            context: ContractContext {
-                contract: campaign_contract.clone(),
-                fn_name: Symbol::new(&env, "add_donation"),
-                args: vec![
-                    &env,
-                    campaign_id.clone().into_val(&env),
-                    amount.into_val(&env),
-                ],
-            },
-            sub_invocations: vec![&env],
-        });
-        env.authorize_as_current_contract(vec![&env, auth_entry]);
+        let clamped = match campaign.hard_cap {
+            Some(cap) => amount.min(cap - campaign.current_amount),
+            None => amount,
+        };
+        if !allocation.is_empty() && amount != clamped {
+            panic_with_error!(&env, DonationError::HardCapReached);
+        }
+        let amount = clamped;
 
         let donation = Donation {
-            campaign_id: campaign_id.clone(),
-            donor: donor.clone(),
+            epoch: Self::current_epoch(env.clone(), campaign_id.clone()),
+            campaign_id,
+            donor,
+            token,
             amount,
+            endowed: endowment_share(amount, campaign.endowment_bps),
             timestamp: env.ledger().timestamp(),
             note,
+            allocation,
+            external: false,
+            campaign_contract: campaign_contract.clone(),
         };
+        Self::record_donation(&env, &campaign_contract, &campaign, donation)
+    }
+
+    pub fn reallocate_donation(
+        env: Env,
+        donor: Address,
+        campaign_id: BytesN<32>,
+        donation_index: u32,
+        new_allocation: Vec<(u32, i128)>,
+    ) -> Donation {
+        donor.require_auth();
 
         let mut donations: Map<Address, Vec<Donation>> = env
             .storage()
             .persistent()
             .get(&campaign_id)
             .unwrap_or_else(|| Map::new(&env));
-
         let mut donor_donations = donations.get(donor.clone()).unwrap_or_else(|| vec![&env]);
-        donor_donations.push_back(donation.clone());
-        donations.set(donor.clone(), donor_donations);
+        let mut donation = donor_donations
+            .get(donation_index)
+            .unwrap_or_else(|| panic_with_error!(&env, DonationError::DonationNotFound));
+        Self::check_allocation(&env, &new_allocation, donation.amount);
+
+        let campaign =
+            CampaignContractClient::new(&env, &donation.campaign_contract).get(&campaign_id);
+        let touched = donation
+            .allocation
+            .iter()
+            .chain(new_allocation.iter())
+            .map(|(index, _)| index);
+        Self::check_milestones_pending(&env, &campaign, touched);
+
+        let earmark_key = (
+            symbol_short!("earmark"),
+            donation.campaign_contract.clone(),
+            campaign_id.clone(),
+        );
+        let mut earmarks: Map<u32, i128> = env
+            .storage()
+            .persistent()
+            .get(&earmark_key)
+            .unwrap_or_else(|| Map::new(&env));
+        for (index, earmark) in donation.allocation.iter() {
+            earmarks.set(index, earmarks.get(index).unwrap_or(0) - earmark);
+        }
+        for (index, earmark) in new_allocation.iter() {
+            earmarks.set(index, earmarks.get(index).unwrap_or(0) + earmark);
+        }
+        env.storage().persistent().set(&earmark_key, &earmarks);
+
+        donation.allocation = new_allocation;
+        donor_donations.set(donation_index, donation.clone());
+        donations.set(donor, donor_donations);
         env.storage().persistent().set(&campaign_id, &donations);
+        donation
+    }
+
+    /// Draws a completed milestone's release from the funds donors earmarked
+    /// for it, and returns how much was covered that way; the rest of the
+    /// release came out of the general pool. Only the campaign's
+    /// verification contract may call this.
+    pub fn draw_earmark(
+        env: Env,
+        campaign_contract: Address,
+        campaign_id: BytesN<32>,
+        milestone_index: u32,
+        amount: i128,
+    ) -> i128 {
+        let campaign = CampaignContractClient::new(&env, &campaign_contract).get(&campaign_id);
+        match campaign.verification_contract {
+            Some(ref contract) => contract.require_auth(),
+            None => panic_with_error!(&env, DonationError::Unauthorized),
+        }
+
+        let earmark_key = (symbol_short!("earmark"), campaign_contract, campaign_id);
+        let mut earmarks: Map<u32, i128> = env
+            .storage()
+            .persistent()
+            .get(&earmark_key)
+            .unwrap_or_else(|| Map::new(&env));
+        let earmarked = earmarks.get(milestone_index).unwrap_or(0);
+        let drawn = earmarked.min(amount).max(0);
+        if drawn > 0 {
+            earmarks.set(milestone_index, earmarked - drawn);
+            env.storage().persistent().set(&earmark_key, &earmarks);
+        }
+        drawn
+    }
+
+    pub fn set_oracle(env: Env, admin: Address, oracle: Option<Address>) {
+        Self::require_admin(&env, &admin);
+        match oracle {
+            Some(oracle) => env
+                .storage()
+                .instance()
+                .set(&symbol_short!("oracle"), &oracle),
+            None => env.storage().instance().remove(&symbol_short!("oracle")),
+        }
+    }
+
+    pub fn record_external_donation(
+        env: Env,
+        oracle: Address,
+        campaign_contract: Address,
+        campaign_id: BytesN<32>,
+        amount: i128,
+        source: String,
+    ) -> Donation {
+        oracle.require_auth();
+        let current: Option<Address> = env.storage().instance().get(&symbol_short!("oracle"));
+        if current != Some(oracle.clone()) {
+            panic_with_error!(&env, DonationError::Unauthorized);
+        }
+
+        let campaign = CampaignContractClient::new(&env, &campaign_contract).get(&campaign_id);
+        Self::require_donatable(&env, &campaign, amount);
+
+        let donation = Donation {
+            epoch: Self::current_epoch(env.clone(), campaign_id.clone()),
+            campaign_id,
+            donor: oracle,
+            token: campaign.token.clone(),
+            amount,
+            endowed: endowment_share(amount, campaign.endowment_bps),
+            timestamp: env.ledger().timestamp(),
+            note: Some(source),
+            allocation: vec![&env],
+            external: true,
+            campaign_contract: campaign_contract.clone(),
+        };
 
-        campaign_client.add_donation(&campaign_id, &amount);
+        let confirmations = Self::confirmations(env.clone(), donation.campaign_id.clone());
+        if confirmations == 0 {
+            let donation = Self::clamp_to_cap(&campaign, donation);
+            return Self::record_donation(&env, &campaign_contract, &campaign, donation);
+        }
 
+        let mut pending = Self::pending_donations(env.clone(), donation.campaign_id.clone());
+        if pending.len() >= MAX_PENDING_DONATIONS {
+            panic_with_error!(&env, DonationError::PendingQueueFull);
+        }
+        pending.push_back(PendingDonation {
+            donation: donation.clone(),
+            confirm_after: env.ledger().sequence().saturating_add(confirmations),
+        });
+        env.storage().persistent().set(
+            &(symbol_short!("pending"), donation.campaign_id.clone()),
+            &pending,
+        );
         donation
     }
 
-    pub fn get_donations(env: Env, campaign_id: BytesN<32>, donor: Address) -> Vec<Donation> {
-        let donations: Map<Address, Vec<Donation>> = env
+    pub fn set_confirmations(env: Env, admin: Address, campaign_id: BytesN<32>, ledgers: u32) {
+        Self::require_admin(&env, &admin);
+
+        env.storage()
+            .persistent()
+            .set(&(symbol_short!("confirms"), campaign_id), &ledgers);
+    }
+
+    pub fn confirmations(env: Env, campaign_id: BytesN<32>) -> u32 {
+        env.storage()
+            .persistent()
+            .get(&(symbol_short!("confirms"), campaign_id))
+            .unwrap_or(0)
+    }
+
+    pub fn pending_donations(env: Env, campaign_id: BytesN<32>) -> Vec<PendingDonation> {
+        env.storage()
+            .persistent()
+            .get(&(symbol_short!("pending"), campaign_id))
+            .unwrap_or_else(|| vec![&env])
+    }
+
+    /// Records every pending external donation whose confirmation window
+    /// has passed, against the campaign contract it was reported for. Like
+    /// `donate`, an entry that would overshoot the hard cap is clamped to
+    /// the remaining room; entries for a full or inactive campaign stay
+    /// queued.
+    pub fn confirm_donations(env: Env, campaign_id: BytesN<32>) -> Vec<Donation> {
+        let sequence = env.ledger().sequence();
+
+        let mut confirmed = vec![&env];
+        let mut remaining = vec![&env];
+        for pending in Self::pending_donations(env.clone(), campaign_id.clone()).iter() {
+            let campaign_contract = pending.donation.campaign_contract.clone();
+            let campaign = CampaignContractClient::new(&env, &campaign_contract).get(&campaign_id);
+            let ready = sequence >= pending.confirm_after
+                && Self::check_donation(&env, &campaign, pending.donation.amount)
+                    == DonateCheck::Ok;
+            if ready {
+                let donation = Self::clamp_to_cap(&campaign, pending.donation);
+                confirmed.push_back(Self::record_donation(
+                    &env,
+                    &campaign_contract,
+                    &campaign,
+                    donation,
+                ));
+            } else {
+                remaining.push_back(pending);
+            }
+        }
+
+        env.storage()
+            .persistent()
+            .set(&(symbol_short!("pending"), campaign_id), &remaining);
+        confirmed
+    }
+
+    pub fn start_epoch(env: Env, admin: Address, campaign_id: BytesN<32>) -> u32 {
+        Self::require_admin(&env, &admin);
+
+        let epoch = Self::current_epoch(env.clone(), campaign_id.clone()) + 1;
+        env.storage()
+            .persistent()
+            .set(&(symbol_short!("epoch"), campaign_id), &epoch);
+        epoch
+    }
+
+    pub fn current_epoch(env: Env, campaign_id: BytesN<32>) -> u32 {
+        env.storage()
+            .persistent()
+            .get(&(symbol_short!("epoch"), campaign_id))
+            .unwrap_or(0)
+    }
+
+    pub fn set_display_name(env: Env, donor: Address, name: String) {
+        donor.require_auth();
+
+        if name.len() > MAX_DISPLAY_NAME_LEN {
+            panic_with_error!(&env, DonationError::NameTooLong);
+        }
+        env.storage()
+            .persistent()
+            .set(&(symbol_short!("name"), donor), &name);
+    }
+
+    pub fn display_name(env: Env, donor: Address) -> Option<String> {
+        env.storage()
+            .persistent()
+            .get(&(symbol_short!("name"), donor))
+    }
+
+    pub fn set_contact_hash(env: Env, donor: Address, contact_hash: BytesN<32>) {
+        donor.require_auth();
+        env.storage()
+            .persistent()
+            .set(&(symbol_short!("contact"), donor), &contact_hash);
+    }
+
+    pub fn get_contact_hash(env: Env, donor: Address) -> Option<BytesN<32>> {
+        env.storage()
+            .persistent()
+            .get(&(symbol_short!("contact"), donor))
+    }
+
+    pub fn top_donors_epoch(env: Env, campaign_id: BytesN<32>, n: u32) -> Vec<TopDonor> {
+        let epoch = Self::current_epoch(env.clone(), campaign_id.clone());
+        let epoch_totals: Map<Address, i128> = env
             .storage()
             .persistent()
-            .get(&campaign_id)
+            .get(&(symbol_short!("ep_tot"), campaign_id, epoch))
             .unwrap_or_else(|| Map::new(&env));
-        donations.get(donor).unwrap_or_else(|| vec![&env])
+
+        let n = n.min(MAX_TOP_DONORS);
+        let mut top: Vec<(Address, i128)> = vec![&env];
+        for (donor, total) in epoch_totals.iter() {
+            let position = top
+                .iter()
+                .position(|(_, ranked)| ranked < total)
+                .map_or(top.len(), |index| index as u32);
+            if position < n {
+                top.insert(position, (donor, total));
+                if top.len() > n {
+                    top.pop_back();
+                }
+            }
+        }
+
+        let mut ranked = vec![&env];
+        for (donor, total) in top.iter() {
+            ranked.push_back(TopDonor {
+                display_name: Self::display_name(env.clone(), donor.clone()),
+                donor,
+                total,
+            });
+        }
+        ranked
     }
 
-    pub fn get_total_donated(env: Env, campaign_id: BytesN<32>) -> i128 {
+    /// Picks a donor weighted by how many donations they made, from the
+    /// sha256 of `seed || ledger sequence`. The ledger alone is predictable,
+    /// so the seed must come from a commit-reveal round or an external
+    /// randomness beacon published before the draw. Donations stored under
+    /// `compact_donations` are not itemized and are not eligible.
+    pub fn pick_random_donor(env: Env, campaign_id: BytesN<32>, seed: BytesN<32>) -> Address {
         let donations: Map<Address, Vec<Donation>> = env
             .storage()
             .persistent()
             .get(&campaign_id)
             .unwrap_or_else(|| Map::new(&env));
+        let entries: u64 = donations
+            .values()
+            .iter()
+            .map(|donor_donations| donor_donations.len() as u64)
+            .sum();
+        if entries == 0 {
+            panic_with_error!(&env, DonationError::NoDonors);
+        }
+
+        let mut data = Bytes::from_array(&env, &seed.to_array());
+        data.extend_from_array(&env.ledger().sequence().to_be_bytes());
+        let hash = env.crypto().sha256(&data).to_array();
+        let mut draw = [0u8; 8];
+        draw.copy_from_slice(&hash[..8]);
+        let mut ticket = u64::from_be_bytes(draw) % entries;
 
-        let mut total = 0;
-        for donor_donations in donations.values() {
-            for donation in donor_donations.iter() {
-                total += donation.amount;
+        for (donor, donor_donations) in donations.iter() {
+            let count = donor_donations.len() as u64;
+            if ticket < count {
+                return donor;
             }
+            ticket -= count;
         }
-        total
+        panic_with_error!(&env, DonationError::NoDonors)
     }
-}
-
-#[cfg(test)]
-mod test {
-    use super::*;
-    use givehub_campaign::{CampaignContract, CampaignStatus};
-    use soroban_sdk::{testutils::Address as _, Env, String};
 
-    #[test]
-    fn test_donation_flow() {
-        let env = Env::default();
-        env.mock_all_auths();
-        let campaign_addr = env.register_contract(None, CampaignContract);
-        let donation_addr = env.register_contract(None, DonationContract);
+    pub fn can_donate(
+        env: Env,
+        campaign_contract: Address,
+        campaign_id: BytesN<32>,
+    ) -> DonateCheck {
+        let campaign = CampaignContractClient::new(&env, &campaign_contract).get(&campaign_id);
+        Self::check_campaign(&env, &campaign)
+    }
 
-        let campaign_client = CampaignContractClient::new(&env, &campaign_addr);
-        let donation_client = DonationContractClient::new(&env, &donation_addr);
+    pub fn simulate_donate(
+        env: Env,
+        campaign_contract: Address,
+        campaign_id: BytesN<32>,
+        donor: Address,
+        amount: i128,
+    ) -> DonateCheck {
+        let campaign_client = CampaignContractClient::new(&env, &campaign_contract);
+        let campaign = campaign_client.get(&campaign_id);
+        let check = Self::check_donation(&env, &campaign, amount);
+        if check != DonateCheck::Ok {
+            return check;
+        }
+        if !Self::is_donor_allowed(env, campaign_contract, campaign_id, donor) {
+            return DonateCheck::DonorNotAllowed;
+        }
+        if campaign_client.is_token_denied(&campaign.token) {
+            return DonateCheck::TokenDenied;
+        }
+        DonateCheck::Ok
+    }
 
-        let creator = Address::generate(&env);
-        let donor = Address::generate(&env);
-        let campaign_id = BytesN::from_array(&env, &[0; 32]);
+    pub fn set_donor_allowlist(
+        env: Env,
+        creator: Address,
+        campaign_contract: Address,
+        campaign_id: BytesN<32>,
+        enforce: bool,
+    ) {
+        Self::require_creator(&env, &creator, &campaign_contract, &campaign_id);
 
-        let _campaign = campaign_client.initialize(
-            &creator,
-            &campaign_id,
-            &String::from_str(&env, "Save the Rainforest"),
-            &String::from_str(&env, "Plant trees"),
-            &500,
+        env.storage().persistent().set(
+            &(symbol_short!("allow_on"), campaign_contract, campaign_id),
+            &enforce,
         );
+    }
 
-        campaign_client.set_authorized_contracts(
-            &creator,
-            &campaign_id,
-            &Some(donation_addr.clone()),
-            &None,
+    pub fn add_allowed_donor(
+        env: Env,
+        creator: Address,
+        campaign_contract: Address,
+        campaign_id: BytesN<32>,
+        donor: Address,
+    ) {
+        Self::require_creator(&env, &creator, &campaign_contract, &campaign_id);
+
+        env.storage().persistent().set(
+            &(
+                symbol_short!("allowed"),
+                campaign_contract,
+                campaign_id,
+                donor,
+            ),
+            &true,
         );
+    }
 
-        let activated = campaign_client.activate(&creator, &campaign_id);
-        assert_eq!(activated.status, CampaignStatus::Active);
+    pub fn remove_allowed_donor(
+        env: Env,
+        creator: Address,
+        campaign_contract: Address,
+        campaign_id: BytesN<32>,
+        donor: Address,
+    ) {
+        Self::require_creator(&env, &creator, &campaign_contract, &campaign_id);
 
-        let donation = donation_client.donate(&donor, &campaign_addr, &campaign_id, &250, &None);
+        env.storage().persistent().remove(&(
+            symbol_short!("allowed"),
+            campaign_contract,
+            campaign_id,
+            donor,
+        ));
+    }
 
-        assert_eq!(donation.amount, 250);
-        assert_eq!(donation.donor, donor);
+    /// Allowlists are keyed by the campaign contract as well as the id, so
+    /// the creator of a look-alike campaign contract cannot gate donations to
+    /// a real campaign that happens to share its id.
+    pub fn is_donor_allowed(
+        env: Env,
+        campaign_contract: Address,
+        campaign_id: BytesN<32>,
+        donor: Address,
+    ) -> bool {
+        let enforced: bool = env
+            .storage()
+            .persistent()
+            .get(&(
+                symbol_short!("allow_on"),
+                campaign_contract.clone(),
+                campaign_id.clone(),
+            ))
+            .unwrap_or(false);
+        !enforced
+            || env.storage().persistent().has(&(
+                symbol_short!("allowed"),
+                campaign_contract,
+                campaign_id,
+                donor,
+            ))
+    }
+
+    pub fn get_donations(env: Env, campaign_id: BytesN<32>, donor: Address) -> Vec<Donation> {
+        let donations: Map<Address, Vec<Donation>> = env
+            .storage()
+            .persistent()
+            .get(&campaign_id)
+            .unwrap_or_else(|| Map::new(&env));
+        donations.get(donor).unwrap_or_else(|| vec![&env])
+    }
+
+    pub fn external_donations(env: Env, campaign_id: BytesN<32>) -> Vec<Donation> {
+        env.storage()
+            .persistent()
+            .get(&(symbol_short!("external"), campaign_id))
+            .unwrap_or_else(|| vec![&env])
+    }
+
+    /// Funds still earmarked for a milestone, net of anything drawn when it
+    /// was completed.
+    pub fn milestone_funding(
+        env: Env,
+        campaign_contract: Address,
+        campaign_id: BytesN<32>,
+        milestone_index: u32,
+    ) -> i128 {
+        let earmarks: Map<u32, i128> = env
+            .storage()
+            .persistent()
+            .get(&(symbol_short!("earmark"), campaign_contract, campaign_id))
+            .unwrap_or_else(|| Map::new(&env));
+        earmarks.get(milestone_index).unwrap_or(0)
+    }
+
+    pub fn campaigns_supported(env: Env, donor: Address) -> Vec<BytesN<32>> {
+        env.storage()
+            .persistent()
+            .get(&(symbol_short!("backed"), donor))
+            .unwrap_or_else(|| vec![&env])
+    }
+
+    pub fn get_donor_aggregate(
+        env: Env,
+        campaign_id: BytesN<32>,
+        donor: Address,
+    ) -> DonorAggregate {
+        env.storage()
+            .persistent()
+            .get(&(symbol_short!("agg"), campaign_id, donor))
+            .unwrap_or(DonorAggregate {
+                total: 0,
+                count: 0,
+                last_timestamp: 0,
+            })
+    }
+
+    pub fn get_total_donated(env: Env, campaign_id: BytesN<32>) -> i128 {
+        env.storage()
+            .persistent()
+            .get(&(symbol_short!("total"), campaign_id.clone()))
+            .unwrap_or_else(|| Self::compute_total_donated(&env, &campaign_id))
+    }
+
+    pub fn get_total_donated_by_token(env: Env, campaign_id: BytesN<32>, token: Address) -> i128 {
+        Self::token_totals(&env, &campaign_id)
+            .get(token)
+            .unwrap_or(0)
+    }
+
+    /// The sha256 of the XDR encoding of the `(itemized donations, external
+    /// donations, total donated)` tuple, which the campaign contract folds
+    /// into its export bundle.
+    pub fn donations_digest(env: Env, campaign_id: BytesN<32>) -> BytesN<32> {
+        let donations: Map<Address, Vec<Donation>> = env
+            .storage()
+            .persistent()
+            .get(&campaign_id)
+            .unwrap_or_else(|| Map::new(&env));
+        let data = (
+            donations,
+            Self::external_donations(env.clone(), campaign_id.clone()),
+            Self::get_total_donated(env.clone(), campaign_id),
+        )
+            .to_xdr(&env);
+        env.crypto().sha256(&data).into()
+    }
+
+    pub fn reconcile(
+        env: Env,
+        campaign_contract: Address,
+        campaign_id: BytesN<32>,
+    ) -> (i128, i128, bool) {
+        let campaign = CampaignContractClient::new(&env, &campaign_contract).get(&campaign_id);
+        let donated = Self::get_total_donated(env, campaign_id);
+        let received = campaign.current_amount + campaign.endowed_amount;
+        (received, donated, received == donated)
+    }
+
+    fn compute_total_donated(env: &Env, campaign_id: &BytesN<32>) -> i128 {
+        Self::token_totals(env, campaign_id).values().iter().sum()
+    }
+
+    /// Per-token totals are kept up to date by `record_donation`, so they
+    /// include compact aggregates. Campaigns recorded before they were
+    /// tracked fall back to summing the itemized and external donations.
+    fn token_totals(env: &Env, campaign_id: &BytesN<32>) -> Map<Address, i128> {
+        if let Some(totals) = env
+            .storage()
+            .persistent()
+            .get(&(symbol_short!("tok_tot"), campaign_id.clone()))
+        {
+            return totals;
+        }
+
+        let donations: Map<Address, Vec<Donation>> = env
+            .storage()
+            .persistent()
+            .get(campaign_id)
+            .unwrap_or_else(|| Map::new(env));
+        let mut totals = Map::new(env);
+        let itemized = donations
+            .values()
+            .iter()
+            .flat_map(|donor_donations| donor_donations.iter())
+            .chain(Self::external_donations(env.clone(), campaign_id.clone()).iter());
+        for donation in itemized {
+            let total = totals.get(donation.token.clone()).unwrap_or(0);
+            totals.set(donation.token, total + donation.amount);
+        }
+        totals
+    }
+
+    pub fn describe_error(_env: Env, code: u32) -> Symbol {
+        match DonationError::try_from(Error::from_contract_error(code)) {
+            Ok(error) => error.symbol(),
+            Err(_) => symbol_short!("unknown"),
+        }
+    }
+
+    fn require_admin(env: &Env, admin: &Address) {
+        givehub_admin::require_admin::<DonationError>(env, admin);
+    }
+
+    fn record_donation(
+        env: &Env,
+        campaign_contract: &Address,
+        campaign: &Campaign,
+        donation: Donation,
+    ) -> Donation {
+        let campaign_id = donation.campaign_id.clone();
+        let donor = donation.donor.clone();
+        let token = donation.token.clone();
+        let amount = donation.amount;
+
+        let auth_entry = InvokerContractAuthEntry::Contract(SubContractInvocation {
+            context: ContractContext {
+                contract: campaign_contract.clone(),
+                fn_name: Symbol::new(env, "add_donation"),
+                args: vec![
+                    env,
+                    campaign_id.clone().into_val(env),
+                    token.clone().into_val(env),
+                    amount.into_val(env),
+                ],
+            },
+            sub_invocations: vec![env],
+        });
+        env.authorize_as_current_contract(vec![env, auth_entry]);
+        CampaignContractClient::new(env, campaign_contract).add_donation(
+            &campaign_id,
+            &token,
+            &amount,
+        );
+
+        let total = Self::get_total_donated(env.clone(), campaign_id.clone()) + amount;
+        env.storage()
+            .persistent()
+            .set(&(symbol_short!("total"), campaign_id.clone()), &total);
+        let mut token_totals = Self::token_totals(env, &campaign_id);
+        let token_total = token_totals.get(token.clone()).unwrap_or(0);
+        token_totals.set(token.clone(), token_total + amount);
+        env.storage().persistent().set(
+            &(symbol_short!("tok_tot"), campaign_id.clone()),
+            &token_totals,
+        );
+
+        // Oracle-reported funds have no real donor, so they stay out of the
+        // donor-facing indexes and leaderboards.
+        if donation.external {
+            let mut external = Self::external_donations(env.clone(), campaign_id.clone());
+            external.push_back(donation.clone());
+            env.storage()
+                .persistent()
+                .set(&(symbol_short!("external"), campaign_id), &external);
+            return donation;
+        }
+
+        let reference = if campaign.compact_donations {
+            let mut aggregate =
+                Self::get_donor_aggregate(env.clone(), campaign_id.clone(), donor.clone());
+            let reference = aggregate.count;
+            aggregate.total += amount;
+            aggregate.count += 1;
+            aggregate.last_timestamp = donation.timestamp;
+            env.storage().persistent().set(
+                &(symbol_short!("agg"), campaign_id.clone(), donor.clone()),
+                &aggregate,
+            );
+            reference
+        } else {
+            let mut donations: Map<Address, Vec<Donation>> = env
+                .storage()
+                .persistent()
+                .get(&campaign_id)
+                .unwrap_or_else(|| Map::new(env));
+
+            let mut donor_donations = donations.get(donor.clone()).unwrap_or_else(|| vec![env]);
+            let reference = donor_donations.len();
+            donor_donations.push_back(donation.clone());
+            donations.set(donor.clone(), donor_donations);
+            env.storage().persistent().set(&campaign_id, &donations);
+            reference
+        };
+
+        let backed_key = (symbol_short!("backed"), donor.clone());
+        let mut backed: Vec<BytesN<32>> = env
+            .storage()
+            .persistent()
+            .get(&backed_key)
+            .unwrap_or_else(|| vec![env]);
+        if !backed.contains(&campaign_id) {
+            backed.push_back(campaign_id.clone());
+            env.storage().persistent().set(&backed_key, &backed);
+        }
+
+        let epoch_key = (symbol_short!("ep_tot"), campaign_id.clone(), donation.epoch);
+        let mut epoch_totals: Map<Address, i128> = env
+            .storage()
+            .persistent()
+            .get(&epoch_key)
+            .unwrap_or_else(|| Map::new(env));
+        let epoch_total = epoch_totals.get(donor.clone()).unwrap_or(0);
+        epoch_totals.set(donor.clone(), epoch_total + amount);
+        env.storage().persistent().set(&epoch_key, &epoch_totals);
+
+        if !donation.allocation.is_empty() {
+            let earmark_key = (
+                symbol_short!("earmark"),
+                campaign_contract.clone(),
+                campaign_id.clone(),
+            );
+            let mut earmarks: Map<u32, i128> = env
+                .storage()
+                .persistent()
+                .get(&earmark_key)
+                .unwrap_or_else(|| Map::new(env));
+            for (index, earmark) in donation.allocation.iter() {
+                earmarks.set(index, earmarks.get(index).unwrap_or(0) + earmark);
+            }
+            env.storage().persistent().set(&earmark_key, &earmarks);
+        }
+
+        env.events().publish(
+            (symbol_short!("receipt"), campaign_id, donor),
+            DonationReceipt {
+                amount,
+                timestamp: donation.timestamp,
+                reference,
+            },
+        );
+
+        donation
+    }
+
+    fn require_creator(
+        env: &Env,
+        creator: &Address,
+        campaign_contract: &Address,
+        campaign_id: &BytesN<32>,
+    ) {
+        creator.require_auth();
+        if CampaignContractClient::new(env, campaign_contract).creator(campaign_id) != *creator {
+            panic_with_error!(env, DonationError::Unauthorized);
+        }
+    }
+
+    fn check_allocation(env: &Env, allocation: &Vec<(u32, i128)>, amount: i128) {
+        let allocated: i128 = allocation.iter().map(|(_, earmark)| earmark).sum();
+        let duplicated = allocation.iter().enumerate().any(|(position, (index, _))| {
+            allocation
+                .iter()
+                .skip(position + 1)
+                .any(|(other, _)| other == index)
+        });
+        if duplicated
+            || allocation.iter().any(|(_, earmark)| earmark <= 0)
+            || (!allocation.is_empty() && allocated != amount)
+        {
+            panic_with_error!(env, DonationError::InvalidAllocation);
+        }
+    }
+
+    fn check_milestones_pending(
+        env: &Env,
+        campaign: &Campaign,
+        indices: impl Iterator<Item = u32>,
+    ) {
+        for index in indices {
+            let verification = campaign
+                .verification_contract
+                .clone()
+                .unwrap_or_else(|| panic_with_error!(env, DonationError::InvalidAllocation));
+            let milestone = VerificationContractClient::new(env, &verification)
+                .try_get_milestone(&campaign.id, &index)
+                .ok()
+                .and_then(|milestone| milestone.ok())
+                .unwrap_or_else(|| panic_with_error!(env, DonationError::InvalidAllocation));
+            if milestone.status != MilestoneStatus::Pending {
+                panic_with_error!(env, DonationError::MilestoneLocked);
+            }
+        }
+    }
+
+    fn clamp_to_cap(campaign: &Campaign, mut donation: Donation) -> Donation {
+        if let Some(cap) = campaign.hard_cap {
+            donation.amount = donation.amount.min(cap - campaign.current_amount);
+            donation.endowed = endowment_share(donation.amount, campaign.endowment_bps);
+        }
+        donation
+    }
+
+    fn require_donatable(env: &Env, campaign: &Campaign, amount: i128) {
+        match Self::check_donation(env, campaign, amount) {
+            DonateCheck::Ok => {}
+            DonateCheck::Unauthorized => panic_with_error!(env, DonationError::Unauthorized),
+            DonateCheck::CampaignInactive => {
+                panic_with_error!(env, DonationError::CampaignInactive)
+            }
+            DonateCheck::HardCapReached => panic_with_error!(env, DonationError::HardCapReached),
+            DonateCheck::InvalidAmount => panic_with_error!(env, DonationError::InvalidAmount),
+            DonateCheck::DonorNotAllowed => {
+                panic_with_error!(env, DonationError::DonorNotAllowed)
+            }
+            DonateCheck::TokenDenied => panic_with_error!(env, DonationError::TokenDenied),
+        }
+    }
+
+    fn check_donation(env: &Env, campaign: &Campaign, amount: i128) -> DonateCheck {
+        if amount <= 0 {
+            return DonateCheck::InvalidAmount;
+        }
+        Self::check_campaign(env, campaign)
+    }
+
+    fn check_campaign(env: &Env, campaign: &Campaign) -> DonateCheck {
+        if campaign.donation_contract != Some(env.current_contract_address()) {
+            return DonateCheck::Unauthorized;
+        }
+        let accepting = match campaign.status {
+            CampaignStatus::Active | CampaignStatus::Funded => true,
+            CampaignStatus::Draft => campaign.auto_activate,
+            _ => false,
+        };
+        if !accepting || campaign.paused {
+            return DonateCheck::CampaignInactive;
+        }
+        if let Some(cap) = campaign.hard_cap {
+            if campaign.current_amount >= cap {
+                return DonateCheck::HardCapReached;
+            }
+        }
+        DonateCheck::Ok
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use givehub_admin::{test_deployed_address, test_salt};
+    use givehub_campaign::{CampaignContract, CampaignError};
+    use givehub_verification::VerificationContract;
+    use soroban_sdk::{
+        testutils::{Address as _, Events, Ledger},
+        Env, String,
+    };
+
+    struct Setup<'a> {
+        env: Env,
+        campaign: CampaignContractClient<'a>,
+        donation: DonationContractClient<'a>,
+        deployer: Address,
+        creator: Address,
+        donor: Address,
+        token: Address,
+        campaign_id: BytesN<32>,
+    }
+
+    impl Setup<'_> {
+        fn init_campaign_admin(&self, admin: &Address) {
+            self.campaign
+                .init_admin(&self.deployer, &test_salt(&self.env, 0), admin);
+        }
+
+        fn init_donation_admin(&self, admin: &Address) {
+            self.donation
+                .init_admin(&self.deployer, &test_salt(&self.env, 1), admin);
+        }
+    }
+
+    impl<'a> Setup<'a> {
+        fn add_milestones(&self, count: u32) -> (VerificationContractClient<'a>, Address) {
+            let verification_addr = self.env.register_contract(None, VerificationContract);
+            let verification = VerificationContractClient::new(&self.env, &verification_addr);
+            let verifier = Address::generate(&self.env);
+            self.campaign.set_authorized_contracts(
+                &self.creator,
+                &self.campaign_id,
+                &Some(self.donation.address.clone()),
+                &Some(verification_addr),
+            );
+            verification.configure_campaign(
+                &self.creator,
+                &self.campaign.address,
+                &self.campaign_id,
+                &verifier,
+                &None,
+            );
+            for _ in 0..count {
+                verification.create_milestone(
+                    &self.creator,
+                    &self.campaign_id,
+                    &String::from_str(&self.env, "Plant trees"),
+                    &100,
+                    &None,
+                );
+            }
+            (verification, verifier)
+        }
+    }
+
+    fn setup<'a>(target: i128) -> Setup<'a> {
+        let env = Env::default();
+        env.mock_all_auths();
+        let deployer = Address::generate(&env);
+        let campaign_addr = env.register_contract(
+            Some(&test_deployed_address(&env, &deployer, 0)),
+            CampaignContract,
+        );
+        let donation_addr = env.register_contract(
+            Some(&test_deployed_address(&env, &deployer, 1)),
+            DonationContract,
+        );
+
+        let campaign = CampaignContractClient::new(&env, &campaign_addr);
+        let donation = DonationContractClient::new(&env, &donation_addr);
+
+        let creator = Address::generate(&env);
+        let donor = Address::generate(&env);
+        let token = env
+            .register_stellar_asset_contract_v2(Address::generate(&env))
+            .address();
+        let campaign_id = BytesN::from_array(&env, &[0; 32]);
+
+        campaign.initialize(
+            &creator,
+            &campaign_id,
+            &String::from_str(&env, "Save the Rainforest"),
+            &String::from_str(&env, "Plant trees"),
+            &target,
+            &Some(token.clone()),
+            &false,
+        );
+        campaign.set_authorized_contracts(&creator, &campaign_id, &Some(donation_addr), &None);
+        campaign.activate(&creator, &campaign_id);
+
+        Setup {
+            env,
+            campaign,
+            donation,
+            deployer,
+            creator,
+            donor,
+            token,
+            campaign_id,
+        }
+    }
+
+    #[test]
+    fn test_donation_flow() {
+        let s = setup(500);
+        let campaign_addr = s.campaign.address.clone();
+        assert_eq!(
+            s.campaign.get(&s.campaign_id).status,
+            CampaignStatus::Active
+        );
+
+        let donation = s.donation.donate(
+            &s.donor,
+            &campaign_addr,
+            &s.campaign_id,
+            &250,
+            &None,
+            &None,
+            &None,
+        );
+
+        assert_eq!(donation.amount, 250);
+        assert_eq!(donation.donor, s.donor);
+        assert_eq!(donation.token, s.token);
+
+        let other_token = Address::generate(&s.env);
+        assert_eq!(
+            s.donation.try_donate(
+                &s.donor,
+                &campaign_addr,
+                &s.campaign_id,
+                &100,
+                &None,
+                &Some(other_token.clone()),
+                &None,
+            ),
+            Err(Ok(DonationError::TokenNotAccepted.into()))
+        );
+        s.donation.donate(
+            &s.donor,
+            &campaign_addr,
+            &s.campaign_id,
+            &100,
+            &None,
+            &Some(s.token.clone()),
+            &None,
+        );
+
+        let total = s.donation.get_total_donated(&s.campaign_id);
+        assert_eq!(total, 350);
+        assert_eq!(
+            s.donation
+                .get_total_donated_by_token(&s.campaign_id, &s.token),
+            350
+        );
+        assert_eq!(
+            s.donation
+                .get_total_donated_by_token(&s.campaign_id, &other_token),
+            0
+        );
+        assert_eq!(s.campaign.balance(&s.campaign_id, &other_token), 0);
+    }
+
+    #[test]
+    fn test_hard_cap_partial_acceptance() {
+        let s = setup(500);
+        let campaign_addr = s.campaign.address.clone();
+        s.campaign
+            .set_hard_cap(&s.creator, &s.campaign_id, &Some(600));
+        assert_eq!(
+            s.donation.can_donate(&campaign_addr, &s.campaign_id),
+            DonateCheck::Ok
+        );
+        s.campaign.pause(&s.creator, &s.campaign_id);
+        assert_eq!(
+            s.donation.can_donate(&campaign_addr, &s.campaign_id),
+            DonateCheck::CampaignInactive
+        );
+        s.campaign.unpause(&s.creator, &s.campaign_id);
+
+        s.donation.donate(
+            &s.donor,
+            &campaign_addr,
+            &s.campaign_id,
+            &450,
+            &None,
+            &None,
+            &None,
+        );
+        let donation = s.donation.donate(
+            &s.donor,
+            &campaign_addr,
+            &s.campaign_id,
+            &400,
+            &None,
+            &None,
+            &None,
+        );
+        assert_eq!(donation.amount, 150);
+        assert_eq!(s.campaign.get(&s.campaign_id).current_amount, 600);
+        assert_eq!(s.donation.get_total_donated(&s.campaign_id), 600);
+
+        assert_eq!(
+            s.donation.can_donate(&campaign_addr, &s.campaign_id),
+            DonateCheck::HardCapReached
+        );
+        assert_eq!(
+            s.donation.try_donate(
+                &s.donor,
+                &campaign_addr,
+                &s.campaign_id,
+                &1,
+                &None,
+                &None,
+                &None
+            ),
+            Err(Ok(DonationError::HardCapReached.into()))
+        );
+
+        s.campaign
+            .set_authorized_contracts(&s.creator, &s.campaign_id, &None, &None);
+        assert_eq!(
+            s.donation.can_donate(&campaign_addr, &s.campaign_id),
+            DonateCheck::Unauthorized
+        );
+    }
+
+    #[contract]
+    pub struct Treasury;
+
+    #[contractimpl]
+    impl Treasury {
+        pub fn fund(
+            env: Env,
+            donation_contract: Address,
+            campaign_contract: Address,
+            campaign_id: BytesN<32>,
+            amount: i128,
+        ) -> Donation {
+            DonationContractClient::new(&env, &donation_contract).donate(
+                &env.current_contract_address(),
+                &campaign_contract,
+                &campaign_id,
+                &amount,
+                &None,
+                &None,
+                &None,
+            )
+        }
+    }
+
+    #[test]
+    fn test_contract_donor() {
+        let s = setup(500);
+        let treasury_addr = s.env.register_contract(None, Treasury);
+        let treasury_client = TreasuryClient::new(&s.env, &treasury_addr);
+
+        // The treasury authorizes as the direct invoker, so no mocked auths are needed.
+        s.env.set_auths(&[]);
+        let donation = treasury_client.fund(
+            &s.donation.address,
+            &s.campaign.address,
+            &s.campaign_id,
+            &300,
+        );
+        assert_eq!(donation.donor, treasury_addr);
+
+        let donations = s.donation.get_donations(&s.campaign_id, &treasury_addr);
+        assert_eq!(donations.len(), 1);
+        assert_eq!(donations.get(0).unwrap(), donation);
+        assert_eq!(s.campaign.get(&s.campaign_id).current_amount, 300);
+    }
+
+    #[test]
+    fn test_donation_receipt_event() {
+        let s = setup(500);
+        let campaign_addr = s.campaign.address.clone();
+
+        s.donation.donate(
+            &s.donor,
+            &campaign_addr,
+            &s.campaign_id,
+            &100,
+            &None,
+            &None,
+            &None,
+        );
+        s.donation.donate(
+            &s.donor,
+            &campaign_addr,
+            &s.campaign_id,
+            &50,
+            &None,
+            &None,
+            &None,
+        );
+
+        let (contract, topics, data) = s.env.events().all().last().unwrap();
+        assert_eq!(contract, s.donation.address);
+        assert_eq!(
+            topics,
+            (symbol_short!("receipt"), s.campaign_id, s.donor).into_val(&s.env)
+        );
+        let receipt: DonationReceipt = data.into_val(&s.env);
+        assert_eq!(
+            receipt,
+            DonationReceipt {
+                amount: 50,
+                timestamp: s.env.ledger().timestamp(),
+                reference: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn test_reconcile() {
+        let s = setup(500);
+        let campaign_addr = s.campaign.address.clone();
+        s.donation.donate(
+            &s.donor,
+            &campaign_addr,
+            &s.campaign_id,
+            &200,
+            &None,
+            &None,
+            &None,
+        );
+        s.donation.donate(
+            &s.donor,
+            &campaign_addr,
+            &s.campaign_id,
+            &50,
+            &None,
+            &None,
+            &None,
+        );
+        assert_eq!(
+            s.donation.reconcile(&campaign_addr, &s.campaign_id),
+            (250, 250, true)
+        );
+
+        // Crediting the campaign outside the donation contract desyncs the two sides.
+        s.campaign.set_authorized_contracts(
+            &s.creator,
+            &s.campaign_id,
+            &Some(Address::generate(&s.env)),
+            &None,
+        );
+        s.campaign.add_donation(&s.campaign_id, &s.token, &10);
+        assert_eq!(
+            s.donation.reconcile(&campaign_addr, &s.campaign_id),
+            (260, 250, false)
+        );
+    }
+
+    #[test]
+    fn test_donation_epochs() {
+        let s = setup(10_000);
+        let campaign_addr = s.campaign.address.clone();
+        let admin = Address::generate(&s.env);
+        let other = Address::generate(&s.env);
+        s.init_donation_admin(&admin);
+
+        s.donation.donate(
+            &s.donor,
+            &campaign_addr,
+            &s.campaign_id,
+            &500,
+            &None,
+            &None,
+            &None,
+        );
+        s.donation.donate(
+            &other,
+            &campaign_addr,
+            &s.campaign_id,
+            &200,
+            &None,
+            &None,
+            &None,
+        );
+        s.donation
+            .set_display_name(&s.donor, &String::from_str(&s.env, "Ada"));
+        assert_eq!(
+            s.donation.top_donors_epoch(&s.campaign_id, &5),
+            vec![
+                &s.env,
+                TopDonor {
+                    donor: s.donor.clone(),
+                    total: 500,
+                    display_name: Some(String::from_str(&s.env, "Ada")),
+                },
+                TopDonor {
+                    donor: other.clone(),
+                    total: 200,
+                    display_name: None,
+                },
+            ]
+        );
+        let long_name = String::from_str(&s.env, &"x".repeat(65));
+        assert_eq!(
+            s.donation.try_set_display_name(&other, &long_name),
+            Err(Ok(DonationError::NameTooLong.into()))
+        );
+
+        assert_eq!(
+            s.donation.try_start_epoch(&s.donor, &s.campaign_id),
+            Err(Ok(DonationError::Unauthorized.into()))
+        );
+        assert_eq!(s.donation.start_epoch(&admin, &s.campaign_id), 1);
+        assert_eq!(s.donation.top_donors_epoch(&s.campaign_id, &5).len(), 0);
+
+        s.donation.donate(
+            &other,
+            &campaign_addr,
+            &s.campaign_id,
+            &100,
+            &None,
+            &None,
+            &None,
+        );
+        let donation = s.donation.donate(
+            &s.donor,
+            &campaign_addr,
+            &s.campaign_id,
+            &50,
+            &None,
+            &None,
+            &None,
+        );
+        assert_eq!(donation.epoch, 1);
+        assert_eq!(
+            s.donation.top_donors_epoch(&s.campaign_id, &1),
+            vec![
+                &s.env,
+                TopDonor {
+                    donor: other.clone(),
+                    total: 100,
+                    display_name: None,
+                },
+            ]
+        );
+
+        // Lifetime history is untouched by the new epoch.
+        assert_eq!(s.donation.get_donations(&s.campaign_id, &s.donor).len(), 2);
+        assert_eq!(s.donation.get_total_donated(&s.campaign_id), 850);
+    }
+
+    #[test]
+    fn test_cached_total_matches_donations() {
+        let s = setup(10_000);
+        let campaign_addr = s.campaign.address.clone();
+        let other = Address::generate(&s.env);
+        for (donor, amount) in [(&s.donor, 120), (&other, 75), (&s.donor, 5)] {
+            s.donation.donate(
+                donor,
+                &campaign_addr,
+                &s.campaign_id,
+                &amount,
+                &None,
+                &None,
+                &None,
+            );
+        }
+
+        let mut computed = 0;
+        for donor in [&s.donor, &other] {
+            for donation in s.donation.get_donations(&s.campaign_id, donor).iter() {
+                computed += donation.amount;
+            }
+        }
+        assert_eq!(computed, 200);
+        assert_eq!(s.donation.get_total_donated(&s.campaign_id), computed);
+    }
+
+    #[test]
+    fn test_compact_donations() {
+        let s = setup(10_000);
+        let campaign_addr = s.campaign.address.clone();
+        assert_eq!(
+            s.campaign
+                .try_set_compact_donations(&s.creator, &s.campaign_id, &true),
+            Err(Ok(CampaignError::NotDraft.into()))
+        );
+
+        let campaign_id = BytesN::from_array(&s.env, &[1; 32]);
+        s.campaign.initialize(
+            &s.creator,
+            &campaign_id,
+            &String::from_str(&s.env, "Clean Water"),
+            &String::from_str(&s.env, "Dig wells"),
+            &10_000,
+            &Some(s.token.clone()),
+            &false,
+        );
+        s.campaign.set_authorized_contracts(
+            &s.creator,
+            &campaign_id,
+            &Some(s.donation.address.clone()),
+            &None,
+        );
+        s.campaign
+            .set_compact_donations(&s.creator, &campaign_id, &true);
+        s.campaign.activate(&s.creator, &campaign_id);
+
+        s.env.ledger().with_mut(|li| li.timestamp = 100);
+        s.donation.donate(
+            &s.donor,
+            &campaign_addr,
+            &campaign_id,
+            &300,
+            &None,
+            &None,
+            &None,
+        );
+        s.env.ledger().with_mut(|li| li.timestamp = 200);
+        s.donation.donate(
+            &s.donor,
+            &campaign_addr,
+            &campaign_id,
+            &200,
+            &None,
+            &None,
+            &None,
+        );
+
+        assert_eq!(
+            s.donation.get_donor_aggregate(&campaign_id, &s.donor),
+            DonorAggregate {
+                total: 500,
+                count: 2,
+                last_timestamp: 200,
+            }
+        );
+        assert_eq!(s.donation.get_donations(&campaign_id, &s.donor).len(), 0);
+        assert_eq!(s.donation.get_total_donated(&campaign_id), 500);
+        assert_eq!(
+            s.donation
+                .get_total_donated_by_token(&campaign_id, &s.token),
+            500
+        );
+        assert_eq!(s.campaign.get(&campaign_id).current_amount, 500);
+    }
+
+    #[test]
+    fn test_pick_random_donor() {
+        let s = setup(10_000);
+        let campaign_addr = s.campaign.address.clone();
+        let seed = BytesN::from_array(&s.env, &[42; 32]);
+        assert_eq!(
+            s.donation.try_pick_random_donor(&s.campaign_id, &seed),
+            Err(Ok(DonationError::NoDonors.into()))
+        );
+
+        let other = Address::generate(&s.env);
+        s.donation.donate(
+            &s.donor,
+            &campaign_addr,
+            &s.campaign_id,
+            &100,
+            &None,
+            &None,
+            &None,
+        );
+        s.donation.donate(
+            &other,
+            &campaign_addr,
+            &s.campaign_id,
+            &100,
+            &None,
+            &None,
+            &None,
+        );
+
+        let winner = s.donation.pick_random_donor(&s.campaign_id, &seed);
+        assert!(winner == s.donor || winner == other);
+        assert_eq!(s.donation.pick_random_donor(&s.campaign_id, &seed), winner);
+
+        let mut winners = vec![&s.env];
+        for byte in 0..16u8 {
+            let seed = BytesN::from_array(&s.env, &[byte; 32]);
+            let winner = s.donation.pick_random_donor(&s.campaign_id, &seed);
+            if !winners.contains(&winner) {
+                winners.push_back(winner);
+            }
+        }
+        assert_eq!(winners.len(), 2);
+    }
+
+    #[test]
+    fn test_describe_error() {
+        let s = setup(1000);
+        assert_eq!(
+            s.donation
+                .describe_error(&(DonationError::Unauthorized as u32)),
+            symbol_short!("unauth")
+        );
+        for code in 1..=14 {
+            assert_ne!(s.donation.describe_error(&code), symbol_short!("unknown"));
+        }
+        assert_eq!(s.donation.describe_error(&15), symbol_short!("unknown"));
+        assert_eq!(s.donation.describe_error(&0), symbol_short!("unknown"));
+    }
+
+    #[test]
+    fn test_milestone_allocation() {
+        let s = setup(1000);
+        let campaign_addr = s.campaign.address.clone();
+        let allocation = vec![&s.env, (0u32, 300i128), (2u32, 100i128)];
+        assert_eq!(
+            s.donation.try_donate(
+                &s.donor,
+                &campaign_addr,
+                &s.campaign_id,
+                &400,
+                &None,
+                &None,
+                &Some(allocation.clone()),
+            ),
+            Err(Ok(DonationError::InvalidAllocation.into()))
+        );
+
+        let (verification, verifier) = s.add_milestones(3);
+        assert_eq!(
+            s.donation.try_donate(
+                &s.donor,
+                &campaign_addr,
+                &s.campaign_id,
+                &500,
+                &None,
+                &None,
+                &Some(allocation.clone()),
+            ),
+            Err(Ok(DonationError::InvalidAllocation.into()))
+        );
+        for bad in [
+            vec![&s.env, (0u32, 300i128), (3u32, 100i128)],
+            vec![&s.env, (0u32, 200i128), (0u32, 200i128)],
+        ] {
+            assert_eq!(
+                s.donation.try_donate(
+                    &s.donor,
+                    &campaign_addr,
+                    &s.campaign_id,
+                    &400,
+                    &None,
+                    &None,
+                    &Some(bad),
+                ),
+                Err(Ok(DonationError::InvalidAllocation.into()))
+            );
+        }
+
+        let donation = s.donation.donate(
+            &s.donor,
+            &campaign_addr,
+            &s.campaign_id,
+            &400,
+            &None,
+            &None,
+            &Some(allocation.clone()),
+        );
+        assert_eq!(donation.allocation, allocation);
+        s.donation.donate(
+            &s.donor,
+            &campaign_addr,
+            &s.campaign_id,
+            &200,
+            &None,
+            &None,
+            &Some(vec![&s.env, (0u32, 200i128)]),
+        );
+        s.donation.donate(
+            &s.donor,
+            &campaign_addr,
+            &s.campaign_id,
+            &50,
+            &None,
+            &None,
+            &None,
+        );
+
+        assert_eq!(
+            s.donation
+                .milestone_funding(&campaign_addr, &s.campaign_id, &0),
+            500
+        );
+        assert_eq!(
+            s.donation
+                .milestone_funding(&campaign_addr, &s.campaign_id, &1),
+            0
+        );
+        assert_eq!(
+            s.donation
+                .milestone_funding(&campaign_addr, &s.campaign_id, &2),
+            100
+        );
+        assert_eq!(s.donation.get_total_donated(&s.campaign_id), 650);
+
+        let docs = vec![&s.env, String::from_str(&s.env, "report.pdf")];
+        verification.verify_milestone(&verifier, &s.campaign_id, &1, &docs, &vec![&s.env]);
+        assert_eq!(
+            s.donation.try_donate(
+                &s.donor,
+                &campaign_addr,
+                &s.campaign_id,
+                &100,
+                &None,
+                &None,
+                &Some(vec![&s.env, (1u32, 100i128)]),
+            ),
+            Err(Ok(DonationError::MilestoneLocked.into()))
+        );
+
+        verification.verify_and_complete(&verifier, &s.campaign_id, &0, &docs, &vec![&s.env]);
+        verification.verify_and_complete(&verifier, &s.campaign_id, &2, &docs, &vec![&s.env]);
+        assert_eq!(
+            s.donation
+                .milestone_funding(&campaign_addr, &s.campaign_id, &0),
+            400
+        );
+        assert_eq!(
+            s.donation
+                .milestone_funding(&campaign_addr, &s.campaign_id, &2),
+            0
+        );
+        assert!(s
+            .donation
+            .mock_auths(&[])
+            .try_draw_earmark(&campaign_addr, &s.campaign_id, &0, &100)
+            .is_err());
+        assert_eq!(
+            s.donation
+                .milestone_funding(&campaign_addr, &s.campaign_id, &0),
+            400
+        );
+    }
+
+    #[test]
+    fn test_reallocate_donation() {
+        let s = setup(1000);
+        let campaign_addr = s.campaign.address.clone();
+        let (verification, verifier) = s.add_milestones(3);
+
+        s.donation.donate(
+            &s.donor,
+            &campaign_addr,
+            &s.campaign_id,
+            &300,
+            &None,
+            &None,
+            &Some(vec![&s.env, (0u32, 300i128)]),
+        );
+        assert_eq!(
+            s.donation.try_reallocate_donation(
+                &s.donor,
+                &s.campaign_id,
+                &1,
+                &vec![&s.env, (1u32, 300i128)],
+            ),
+            Err(Ok(DonationError::DonationNotFound.into()))
+        );
+        assert_eq!(
+            s.donation.try_reallocate_donation(
+                &s.donor,
+                &s.campaign_id,
+                &0,
+                &vec![&s.env, (1u32, 200i128)],
+            ),
+            Err(Ok(DonationError::InvalidAllocation.into()))
+        );
+        assert_eq!(
+            s.donation.try_reallocate_donation(
+                &s.donor,
+                &s.campaign_id,
+                &0,
+                &vec![&s.env, (5u32, 300i128)],
+            ),
+            Err(Ok(DonationError::InvalidAllocation.into()))
+        );
+
+        let new_allocation = vec![&s.env, (1u32, 100i128), (2u32, 200i128)];
+        let donation =
+            s.donation
+                .reallocate_donation(&s.donor, &s.campaign_id, &0, &new_allocation);
+        assert_eq!(donation.allocation, new_allocation);
+        assert_eq!(donation.campaign_contract, campaign_addr);
+        assert_eq!(
+            s.donation.get_donations(&s.campaign_id, &s.donor),
+            vec![&s.env, donation]
+        );
+        assert_eq!(
+            s.donation
+                .milestone_funding(&campaign_addr, &s.campaign_id, &0),
+            0
+        );
+        assert_eq!(
+            s.donation
+                .milestone_funding(&campaign_addr, &s.campaign_id, &1),
+            100
+        );
+        assert_eq!(
+            s.donation
+                .milestone_funding(&campaign_addr, &s.campaign_id, &2),
+            200
+        );
+
+        let docs = vec![&s.env, String::from_str(&s.env, "report.pdf")];
+        verification.verify_milestone(&verifier, &s.campaign_id, &2, &docs, &vec![&s.env]);
+        assert_eq!(
+            s.donation.try_reallocate_donation(
+                &s.donor,
+                &s.campaign_id,
+                &0,
+                &vec![&s.env, (0u32, 300i128)],
+            ),
+            Err(Ok(DonationError::MilestoneLocked.into()))
+        );
+    }
+
+    #[test]
+    fn test_donor_allowlist() {
+        let s = setup(1000);
+        let campaign_addr = s.campaign.address.clone();
+        let outsider = Address::generate(&s.env);
+        s.donation.donate(
+            &outsider,
+            &campaign_addr,
+            &s.campaign_id,
+            &100,
+            &None,
+            &None,
+            &None,
+        );
+
+        assert_eq!(
+            s.donation
+                .try_set_donor_allowlist(&outsider, &campaign_addr, &s.campaign_id, &true),
+            Err(Ok(DonationError::Unauthorized.into()))
+        );
+        s.donation
+            .set_donor_allowlist(&s.creator, &campaign_addr, &s.campaign_id, &true);
+        s.donation
+            .add_allowed_donor(&s.creator, &campaign_addr, &s.campaign_id, &s.donor);
+
+        assert_eq!(
+            s.donation.try_donate(
+                &outsider,
+                &campaign_addr,
+                &s.campaign_id,
+                &100,
+                &None,
+                &None,
+                &None,
+            ),
+            Err(Ok(DonationError::DonorNotAllowed.into()))
+        );
+        assert_eq!(
+            s.donation
+                .simulate_donate(&campaign_addr, &s.campaign_id, &outsider, &100),
+            DonateCheck::DonorNotAllowed
+        );
+        s.donation.donate(
+            &s.donor,
+            &campaign_addr,
+            &s.campaign_id,
+            &200,
+            &None,
+            &None,
+            &None,
+        );
+
+        s.donation
+            .remove_allowed_donor(&s.creator, &campaign_addr, &s.campaign_id, &s.donor);
+        assert!(!s
+            .donation
+            .is_donor_allowed(&campaign_addr, &s.campaign_id, &s.donor));
+        s.donation
+            .set_donor_allowlist(&s.creator, &campaign_addr, &s.campaign_id, &false);
+        assert!(s
+            .donation
+            .is_donor_allowed(&campaign_addr, &s.campaign_id, &outsider));
+        assert_eq!(s.donation.get_total_donated(&s.campaign_id), 300);
+    }
+
+    #[contract]
+    pub struct FakeCampaign;
+
+    #[contractimpl]
+    impl FakeCampaign {
+        pub fn set_creator(env: Env, creator: Address) {
+            env.storage()
+                .instance()
+                .set(&symbol_short!("creator"), &creator);
+        }
+
+        pub fn creator(env: Env, _campaign_id: BytesN<32>) -> Address {
+            env.storage()
+                .instance()
+                .get(&symbol_short!("creator"))
+                .unwrap()
+        }
+    }
+
+    #[test]
+    fn test_fake_campaign_cannot_gate_real_allowlist() {
+        let s = setup(1000);
+        let campaign_addr = s.campaign.address.clone();
+        let attacker = Address::generate(&s.env);
+        let fake_addr = s.env.register_contract(None, FakeCampaign);
+        FakeCampaignClient::new(&s.env, &fake_addr).set_creator(&attacker);
+
+        // The attacker passes the fake contract's creator check for the real id.
+        s.donation
+            .set_donor_allowlist(&attacker, &fake_addr, &s.campaign_id, &true);
+        assert!(!s
+            .donation
+            .is_donor_allowed(&fake_addr, &s.campaign_id, &s.donor));
+
+        assert!(s
+            .donation
+            .is_donor_allowed(&campaign_addr, &s.campaign_id, &s.donor));
+        assert_eq!(
+            s.donation
+                .simulate_donate(&campaign_addr, &s.campaign_id, &s.donor, &100),
+            DonateCheck::Ok
+        );
+        let donation = s.donation.donate(
+            &s.donor,
+            &campaign_addr,
+            &s.campaign_id,
+            &100,
+            &None,
+            &None,
+            &None,
+        );
+        assert_eq!(donation.amount, 100);
+    }
+
+    #[test]
+    fn test_denied_token() {
+        let s = setup(1000);
+        let campaign_addr = s.campaign.address.clone();
+        let admin = Address::generate(&s.env);
+        let other_token = Address::generate(&s.env);
+        s.init_campaign_admin(&admin);
+        s.campaign.set_token_denied(&admin, &other_token, &true);
+
+        assert_eq!(
+            s.donation.try_donate(
+                &s.donor,
+                &campaign_addr,
+                &s.campaign_id,
+                &100,
+                &None,
+                &Some(other_token),
+                &None,
+            ),
+            Err(Ok(DonationError::TokenDenied.into()))
+        );
+        s.donation.donate(
+            &s.donor,
+            &campaign_addr,
+            &s.campaign_id,
+            &100,
+            &None,
+            &None,
+            &None,
+        );
+
+        s.campaign.set_token_denied(&admin, &s.token, &true);
+        assert_eq!(
+            s.donation
+                .simulate_donate(&campaign_addr, &s.campaign_id, &s.donor, &100),
+            DonateCheck::TokenDenied
+        );
+        assert_eq!(
+            s.donation.try_donate(
+                &s.donor,
+                &campaign_addr,
+                &s.campaign_id,
+                &100,
+                &None,
+                &None,
+                &None,
+            ),
+            Err(Ok(DonationError::TokenDenied.into()))
+        );
+        assert_eq!(s.donation.get_total_donated(&s.campaign_id), 100);
+    }
+
+    #[test]
+    fn test_campaigns_supported() {
+        let s = setup(10_000);
+        let campaign_addr = s.campaign.address.clone();
+        assert_eq!(s.donation.campaigns_supported(&s.donor).len(), 0);
+
+        let second_id = BytesN::from_array(&s.env, &[9; 32]);
+        let title = String::from_str(&s.env, "Clean water");
+        s.campaign.initialize(
+            &s.creator,
+            &second_id,
+            &title,
+            &title,
+            &500,
+            &Some(s.token.clone()),
+            &false,
+        );
+        s.campaign.set_authorized_contracts(
+            &s.creator,
+            &second_id,
+            &Some(s.donation.address.clone()),
+            &None,
+        );
+        s.campaign.activate(&s.creator, &second_id);
+
+        for campaign_id in [&s.campaign_id, &second_id, &s.campaign_id] {
+            s.donation.donate(
+                &s.donor,
+                &campaign_addr,
+                campaign_id,
+                &100,
+                &None,
+                &None,
+                &None,
+            );
+        }
+        assert_eq!(
+            s.donation.campaigns_supported(&s.donor),
+            vec![&s.env, s.campaign_id.clone(), second_id]
+        );
+    }
+
+    #[test]
+    fn test_simulate_donate() {
+        let s = setup(1000);
+        let campaign_addr = s.campaign.address.clone();
+        s.campaign
+            .set_hard_cap(&s.creator, &s.campaign_id, &Some(1000));
+
+        assert_eq!(
+            s.donation
+                .simulate_donate(&campaign_addr, &s.campaign_id, &s.donor, &0),
+            DonateCheck::InvalidAmount
+        );
+        assert_eq!(
+            s.donation
+                .simulate_donate(&campaign_addr, &s.campaign_id, &s.donor, &100),
+            DonateCheck::Ok
+        );
+
+        s.campaign.pause(&s.creator, &s.campaign_id);
+        assert_eq!(
+            s.donation
+                .simulate_donate(&campaign_addr, &s.campaign_id, &s.donor, &100),
+            DonateCheck::CampaignInactive
+        );
+        s.campaign.unpause(&s.creator, &s.campaign_id);
+
+        s.donation.donate(
+            &s.donor,
+            &campaign_addr,
+            &s.campaign_id,
+            &1000,
+            &None,
+            &None,
+            &None,
+        );
+        assert_eq!(
+            s.donation
+                .simulate_donate(&campaign_addr, &s.campaign_id, &s.donor, &100),
+            DonateCheck::HardCapReached
+        );
+        assert_eq!(
+            s.donation.try_donate(
+                &s.donor,
+                &campaign_addr,
+                &s.campaign_id,
+                &100,
+                &None,
+                &None,
+                &None
+            ),
+            Err(Ok(DonationError::HardCapReached.into()))
+        );
+    }
+
+    #[test]
+    fn test_record_external_donation() {
+        let s = setup(1000);
+        let campaign_addr = s.campaign.address.clone();
+        let admin = Address::generate(&s.env);
+        let oracle = Address::generate(&s.env);
+        let source = String::from_str(&s.env, "ACME corporate match");
+
+        assert_eq!(
+            s.donation.try_record_external_donation(
+                &oracle,
+                &campaign_addr,
+                &s.campaign_id,
+                &300,
+                &source
+            ),
+            Err(Ok(DonationError::Unauthorized.into()))
+        );
+        s.init_donation_admin(&admin);
+        s.donation.set_oracle(&admin, &Some(oracle.clone()));
+
+        let donation = s.donation.record_external_donation(
+            &oracle,
+            &campaign_addr,
+            &s.campaign_id,
+            &300,
+            &source,
+        );
+        assert!(donation.external);
+        assert_eq!(donation.note, Some(source));
+        s.donation.donate(
+            &s.donor,
+            &campaign_addr,
+            &s.campaign_id,
+            &200,
+            &None,
+            &None,
+            &None,
+        );
+
+        assert_eq!(s.campaign.get(&s.campaign_id).current_amount, 500);
+        assert_eq!(s.donation.get_total_donated(&s.campaign_id), 500);
+        assert_eq!(
+            s.donation
+                .get_total_donated_by_token(&s.campaign_id, &s.token),
+            500
+        );
+        assert!(
+            !s.donation
+                .get_donations(&s.campaign_id, &s.donor)
+                .get_unchecked(0)
+                .external
+        );
+
+        // The oracle is not a donor and never shows up in donor-facing views.
+        assert_eq!(
+            s.donation.external_donations(&s.campaign_id),
+            vec![&s.env, donation]
+        );
+        assert!(s.donation.get_donations(&s.campaign_id, &oracle).is_empty());
+        assert!(s.donation.campaigns_supported(&oracle).is_empty());
+        let top = s.donation.top_donors_epoch(&s.campaign_id, &10);
+        assert_eq!(top.len(), 1);
+        assert_eq!(top.get_unchecked(0).donor, s.donor);
+        for seed in 0..8u8 {
+            assert_eq!(
+                s.donation
+                    .pick_random_donor(&s.campaign_id, &BytesN::from_array(&s.env, &[seed; 32])),
+                s.donor
+            );
+        }
+
+        s.campaign
+            .set_hard_cap(&s.creator, &s.campaign_id, &Some(1000));
+        let clamped = s.donation.record_external_donation(
+            &oracle,
+            &campaign_addr,
+            &s.campaign_id,
+            &900,
+            &String::from_str(&s.env, "ACME second match"),
+        );
+        assert_eq!(clamped.amount, 500);
+        assert_eq!(s.campaign.get(&s.campaign_id).current_amount, 1000);
+    }
+
+    #[test]
+    fn test_external_donation_confirmations() {
+        let s = setup(1000);
+        let campaign_addr = s.campaign.address.clone();
+        let admin = Address::generate(&s.env);
+        let oracle = Address::generate(&s.env);
+        let source = String::from_str(&s.env, "bridge tx 42");
+        s.init_donation_admin(&admin);
+        s.donation.set_oracle(&admin, &Some(oracle.clone()));
+        s.donation.set_confirmations(&admin, &s.campaign_id, &5);
+        s.env.ledger().with_mut(|li| li.sequence_number = 100);
+
+        s.donation
+            .record_external_donation(&oracle, &campaign_addr, &s.campaign_id, &300, &source);
+        s.donation.donate(
+            &s.donor,
+            &campaign_addr,
+            &s.campaign_id,
+            &200,
+            &None,
+            &None,
+            &None,
+        );
+        let pending = s.donation.pending_donations(&s.campaign_id);
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending.get_unchecked(0).confirm_after, 105);
+        assert_eq!(
+            pending.get_unchecked(0).donation.campaign_contract,
+            campaign_addr
+        );
+        assert_eq!(s.campaign.get(&s.campaign_id).current_amount, 200);
+
+        s.env.ledger().with_mut(|li| li.sequence_number = 104);
+        assert!(s.donation.confirm_donations(&s.campaign_id).is_empty());
+        assert_eq!(s.campaign.get(&s.campaign_id).current_amount, 200);
+
+        // The cap fills up while the entry waits, so it is clamped on confirmation.
+        s.campaign
+            .set_hard_cap(&s.creator, &s.campaign_id, &Some(1000));
+        s.donation.donate(
+            &s.donor,
+            &campaign_addr,
+            &s.campaign_id,
+            &600,
+            &None,
+            &None,
+            &None,
+        );
+        s.env.ledger().with_mut(|li| li.sequence_number = 105);
+        let confirmed = s.donation.confirm_donations(&s.campaign_id);
+        assert_eq!(confirmed.len(), 1);
+        assert_eq!(confirmed.get_unchecked(0).amount, 200);
+        assert!(s.donation.pending_donations(&s.campaign_id).is_empty());
+        assert_eq!(s.campaign.get(&s.campaign_id).current_amount, 1000);
+        assert_eq!(s.donation.get_total_donated(&s.campaign_id), 1000);
+    }
+
+    #[test]
+    fn test_pending_queue_bounds() {
+        let s = setup(1_000_000);
+        let campaign_addr = s.campaign.address.clone();
+        let admin = Address::generate(&s.env);
+        let oracle = Address::generate(&s.env);
+        let source = String::from_str(&s.env, "ACME corporate match");
+        s.init_donation_admin(&admin);
+        s.donation.set_oracle(&admin, &Some(oracle.clone()));
+        s.donation
+            .set_confirmations(&admin, &s.campaign_id, &u32::MAX);
+        s.env.ledger().with_mut(|li| li.sequence_number = 100);
+
+        for _ in 0..MAX_PENDING_DONATIONS {
+            s.donation.record_external_donation(
+                &oracle,
+                &campaign_addr,
+                &s.campaign_id,
+                &10,
+                &source,
+            );
+        }
+        let pending = s.donation.pending_donations(&s.campaign_id);
+        assert_eq!(pending.len(), MAX_PENDING_DONATIONS);
+        assert_eq!(pending.get_unchecked(0).confirm_after, u32::MAX);
+        assert_eq!(
+            s.donation.try_record_external_donation(
+                &oracle,
+                &campaign_addr,
+                &s.campaign_id,
+                &10,
+                &source
+            ),
+            Err(Ok(DonationError::PendingQueueFull.into()))
+        );
+    }
+
+    #[test]
+    fn test_contact_hash() {
+        let s = setup(1000);
+        assert_eq!(s.donation.get_contact_hash(&s.donor), None);
 
-        let total = donation_client.get_total_donated(&campaign_id);
-        assert_eq!(total, 250);
+        let contact = Bytes::from_slice(&s.env, b"donor@example.org");
+        let contact_hash: BytesN<32> = s.env.crypto().sha256(&contact).into();
+        s.donation.set_contact_hash(&s.donor, &contact_hash);
+        assert_eq!(s.donation.get_contact_hash(&s.donor), Some(contact_hash));
     }
 }
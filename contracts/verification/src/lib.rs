@@ -1,9 +1,11 @@
 #![no_std]
-use givehub_campaign::CampaignContractClient;
+use givehub_admin::AdminError;
+use givehub_campaign::{CampaignContractClient, CampaignStatus, MAX_BPS};
 use soroban_sdk::{
     auth::{ContractContext, InvokerContractAuthEntry, SubContractInvocation},
     contract, contracterror, contractimpl, contracttype, panic_with_error, symbol_short, vec,
-    Address, BytesN, Env, IntoVal, String, Symbol, Vec,
+    xdr::ToXdr,
+    Address, Bytes, BytesN, Env, Error, IntoVal, InvokeError, Map, String, Symbol, Vec,
 };
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
@@ -15,15 +17,45 @@ pub enum MilestoneStatus {
     Failed,
 }
 
+pub const DEFAULT_MAX_MILESTONES: u32 = 50;
+pub const MAX_MILESTONES_LIMIT: u32 = 200;
+pub const DEFAULT_MAX_DOC_BYTES: u32 = 4096;
+pub const DEFAULT_MIN_DOCS: u32 = 1;
+pub const DEFAULT_MIN_MILESTONES: u32 = 1;
+pub const MAX_CHECKIN_NOTE_LEN: u32 = 256;
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 #[contracttype]
 pub struct Milestone {
+    pub id: u32,
     pub description: String,
     pub amount: i128,
     pub status: MilestoneStatus,
     pub verification_docs: Vec<String>,
+    pub doc_hashes: Vec<BytesN<32>>,
     pub verified_by: Option<Address>,
+    pub needs_more_info: bool,
+    pub info_request: Option<String>,
+    pub depends_on: Option<u32>,
+    pub created_at: u64,
+    pub verified_at: Option<u64>,
     pub completed_at: Option<u64>,
+    pub nonce: u32,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct Tombstone {
+    pub purged_at: u64,
+    pub reason: CampaignStatus,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct CheckIn {
+    pub verifier: Address,
+    pub note: String,
+    pub timestamp: u64,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -32,6 +64,28 @@ pub struct VerificationConfig {
     pub campaign_contract: Address,
     pub owner: Address,
     pub verifier: Address,
+    pub max_milestones: u32,
+    pub max_doc_bytes: u32,
+    pub min_docs: u32,
+    pub min_milestones: u32,
+    pub docs_valid_secs: Option<u64>,
+    pub verifier_keys: Map<Address, BytesN<32>>,
+    pub threshold: u32,
+    pub require_funded_to_complete: bool,
+    pub failure_threshold: u32,
+    pub approval_ttl_secs: Option<u64>,
+}
+
+/// The single-verifier layout stored under `("cfg", campaign_id)` by
+/// deployments that predate multisig verification. `read_config` upgrades it
+/// on the fly and `migrate_config` rewrites it in the current layout under
+/// `("cfg_v2", campaign_id)`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct VerificationConfigV1 {
+    pub campaign_contract: Address,
+    pub owner: Address,
+    pub verifier: Address,
 }
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
@@ -44,6 +98,68 @@ pub enum VerificationError {
     MilestoneNotVerified = 4,
     Unauthorized = 5,
     NotConfigured = 6,
+    TooManyMilestones = 7,
+    NoMilestones = 8,
+    InsufficientFunds = 9,
+    DocsTooLarge = 10,
+    AdminNotSet = 11,
+    InsufficientDocs = 12,
+    CampaignNotFinished = 13,
+    TooFewMilestones = 14,
+    DocsExpired = 15,
+    InvalidThreshold = 16,
+    DuplicateSigner = 17,
+    InsufficientSignatures = 18,
+    DependencyNotMet = 19,
+    NoteTooLong = 20,
+    Purged = 21,
+    CampaignNotFunded = 22,
+    VerifierBlacklisted = 23,
+    CampaignCallFailed = 24,
+    ApprovalNotStale = 25,
+    InvalidMaxMilestones = 26,
+}
+
+impl VerificationError {
+    fn symbol(self) -> Symbol {
+        match self {
+            VerificationError::InvalidAmount => symbol_short!("bad_amt"),
+            VerificationError::MilestoneNotFound => symbol_short!("no_ms"),
+            VerificationError::MilestoneNotPending => symbol_short!("not_pend"),
+            VerificationError::MilestoneNotVerified => symbol_short!("not_verif"),
+            VerificationError::Unauthorized => symbol_short!("unauth"),
+            VerificationError::NotConfigured => symbol_short!("no_cfg"),
+            VerificationError::TooManyMilestones => symbol_short!("too_many"),
+            VerificationError::NoMilestones => symbol_short!("no_ms_yet"),
+            VerificationError::InsufficientFunds => symbol_short!("no_funds"),
+            VerificationError::DocsTooLarge => symbol_short!("docs_big"),
+            VerificationError::AdminNotSet => symbol_short!("no_admin"),
+            VerificationError::InsufficientDocs => symbol_short!("few_docs"),
+            VerificationError::CampaignNotFinished => symbol_short!("not_done"),
+            VerificationError::TooFewMilestones => symbol_short!("too_few"),
+            VerificationError::DocsExpired => symbol_short!("docs_old"),
+            VerificationError::InvalidThreshold => symbol_short!("bad_thres"),
+            VerificationError::DuplicateSigner => symbol_short!("dup_sig"),
+            VerificationError::InsufficientSignatures => symbol_short!("few_sigs"),
+            VerificationError::DependencyNotMet => symbol_short!("dep_unmet"),
+            VerificationError::NoteTooLong => symbol_short!("long_note"),
+            VerificationError::Purged => symbol_short!("purged"),
+            VerificationError::CampaignNotFunded => symbol_short!("not_fundd"),
+            VerificationError::VerifierBlacklisted => symbol_short!("blacklist"),
+            VerificationError::CampaignCallFailed => symbol_short!("call_fail"),
+            VerificationError::ApprovalNotStale => symbol_short!("not_stale"),
+            VerificationError::InvalidMaxMilestones => symbol_short!("bad_max"),
+        }
+    }
+}
+
+impl From<AdminError> for VerificationError {
+    fn from(error: AdminError) -> Self {
+        match error {
+            AdminError::Unauthorized => VerificationError::Unauthorized,
+            AdminError::AdminNotSet => VerificationError::AdminNotSet,
+        }
+    }
 }
 
 #[contract]
@@ -51,29 +167,193 @@ pub struct VerificationContract;
 
 #[contractimpl]
 impl VerificationContract {
+    pub fn init_admin(env: Env, deployer: Address, salt: BytesN<32>, admin: Address) {
+        givehub_admin::init_admin::<VerificationError>(&env, deployer, salt, admin);
+    }
+
+    pub fn set_admin(env: Env, admin: Address) {
+        givehub_admin::set_admin::<VerificationError>(&env, admin);
+    }
+
+    pub fn set_verifier_blacklisted(
+        env: Env,
+        admin: Address,
+        verifier: Address,
+        blacklisted: bool,
+    ) {
+        Self::require_admin(&env, &admin);
+
+        let key = (symbol_short!("blocked"), verifier);
+        if blacklisted {
+            env.storage().persistent().set(&key, &true);
+        } else {
+            env.storage().persistent().remove(&key);
+        }
+    }
+
+    pub fn is_verifier_blacklisted(env: Env, verifier: Address) -> bool {
+        env.storage()
+            .persistent()
+            .has(&(symbol_short!("blocked"), verifier))
+    }
+
     pub fn configure_campaign(
         env: Env,
         owner: Address,
         campaign_contract: Address,
         campaign_id: BytesN<32>,
         verifier: Address,
+        max_milestones: Option<u32>,
     ) -> VerificationConfig {
         owner.require_auth();
 
         let campaign_client = CampaignContractClient::new(&env, &campaign_contract);
-        let campaign_owner = campaign_client.creator(&campaign_id);
+        let campaign_owner = Self::campaign_call(&env, campaign_client.try_creator(&campaign_id));
         if campaign_owner != owner {
             panic_with_error!(&env, VerificationError::Unauthorized);
         }
+        Self::check_not_blacklisted(&env, &verifier);
+        let max_milestones = max_milestones.unwrap_or(DEFAULT_MAX_MILESTONES);
+        if max_milestones == 0 || max_milestones > MAX_MILESTONES_LIMIT {
+            panic_with_error!(&env, VerificationError::InvalidMaxMilestones);
+        }
 
-        let config = VerificationConfig {
-            campaign_contract,
-            owner,
-            verifier,
-        };
+        let config = Self::default_config(&env, campaign_contract, owner, verifier, max_milestones);
+
+        Self::save_config(&env, &campaign_id, &config);
+        config
+    }
+
+    /// Rewrites a config stored in the single-verifier layout into the
+    /// current one. Running it again, or on a config that was never in the
+    /// old layout, leaves the stored config unchanged.
+    pub fn migrate_config(env: Env, owner: Address, campaign_id: BytesN<32>) -> VerificationConfig {
+        let config = Self::read_owned_config(&env, &owner, &campaign_id);
+        Self::save_config(&env, &campaign_id, &config);
+        config
+    }
+
+    pub fn clone_config(
+        env: Env,
+        owner: Address,
+        source_id: BytesN<32>,
+        new_id: BytesN<32>,
+    ) -> VerificationConfig {
+        let config = Self::read_owned_config(&env, &owner, &source_id);
+
+        let campaign_client = CampaignContractClient::new(&env, &config.campaign_contract);
+        if Self::campaign_call(&env, campaign_client.try_creator(&new_id)) != owner {
+            panic_with_error!(&env, VerificationError::Unauthorized);
+        }
+        Self::check_not_blacklisted(&env, &config.verifier);
+
+        Self::save_config(&env, &new_id, &config);
+        config
+    }
+
+    pub fn set_doc_budget(
+        env: Env,
+        admin: Address,
+        campaign_id: BytesN<32>,
+        max_doc_bytes: u32,
+    ) -> VerificationConfig {
+        Self::require_admin(&env, &admin);
+
+        let mut config = Self::read_config(&env, &campaign_id);
+        config.max_doc_bytes = max_doc_bytes;
+        Self::save_config(&env, &campaign_id, &config);
+        config
+    }
+
+    pub fn set_min_docs(
+        env: Env,
+        owner: Address,
+        campaign_id: BytesN<32>,
+        min_docs: u32,
+    ) -> VerificationConfig {
+        let mut config = Self::read_owned_config(&env, &owner, &campaign_id);
+        config.min_docs = min_docs;
+        Self::save_config(&env, &campaign_id, &config);
+        config
+    }
+
+    pub fn set_require_funded_to_complete(
+        env: Env,
+        owner: Address,
+        campaign_id: BytesN<32>,
+        required: bool,
+    ) -> VerificationConfig {
+        let mut config = Self::read_owned_config(&env, &owner, &campaign_id);
+        config.require_funded_to_complete = required;
+        Self::save_config(&env, &campaign_id, &config);
+        config
+    }
+
+    pub fn set_approval_ttl(
+        env: Env,
+        owner: Address,
+        campaign_id: BytesN<32>,
+        approval_ttl_secs: Option<u64>,
+    ) -> VerificationConfig {
+        let mut config = Self::read_owned_config(&env, &owner, &campaign_id);
+        config.approval_ttl_secs = approval_ttl_secs;
+        Self::save_config(&env, &campaign_id, &config);
+        config
+    }
+
+    pub fn set_failure_threshold(
+        env: Env,
+        owner: Address,
+        campaign_id: BytesN<32>,
+        failure_threshold: u32,
+    ) -> VerificationConfig {
+        let mut config = Self::read_owned_config(&env, &owner, &campaign_id);
+        config.failure_threshold = failure_threshold;
+        Self::save_config(&env, &campaign_id, &config);
+        config
+    }
 
-        let key = (symbol_short!("cfg"), campaign_id.clone());
-        env.storage().persistent().set(&key, &config);
+    pub fn set_min_milestones(
+        env: Env,
+        owner: Address,
+        campaign_id: BytesN<32>,
+        min_milestones: u32,
+    ) -> VerificationConfig {
+        let mut config = Self::read_owned_config(&env, &owner, &campaign_id);
+        if min_milestones > config.max_milestones {
+            panic_with_error!(&env, VerificationError::TooManyMilestones);
+        }
+        config.min_milestones = min_milestones;
+        Self::save_config(&env, &campaign_id, &config);
+        config
+    }
+
+    pub fn set_verifier_set(
+        env: Env,
+        owner: Address,
+        campaign_id: BytesN<32>,
+        verifier_keys: Map<Address, BytesN<32>>,
+        threshold: u32,
+    ) -> VerificationConfig {
+        let mut config = Self::read_owned_config(&env, &owner, &campaign_id);
+        if threshold == 0 || threshold > verifier_keys.len() {
+            panic_with_error!(&env, VerificationError::InvalidThreshold);
+        }
+        config.verifier_keys = verifier_keys;
+        config.threshold = threshold;
+        Self::save_config(&env, &campaign_id, &config);
+        config
+    }
+
+    pub fn set_docs_valid_secs(
+        env: Env,
+        owner: Address,
+        campaign_id: BytesN<32>,
+        docs_valid_secs: Option<u64>,
+    ) -> VerificationConfig {
+        let mut config = Self::read_owned_config(&env, &owner, &campaign_id);
+        config.docs_valid_secs = docs_valid_secs;
+        Self::save_config(&env, &campaign_id, &config);
         config
     }
 
@@ -83,8 +363,9 @@ impl VerificationContract {
         campaign_id: BytesN<32>,
         description: String,
         amount: i128,
+        depends_on: Option<u32>,
     ) -> Milestone {
-        if amount <= 0 {
+        if amount < 0 {
             panic_with_error!(&env, VerificationError::InvalidAmount);
         }
 
@@ -94,13 +375,23 @@ impl VerificationContract {
             panic_with_error!(&env, VerificationError::Unauthorized);
         }
 
+        let id_key = (symbol_short!("ms_next"), campaign_id.clone());
+        let id: u32 = env.storage().persistent().get(&id_key).unwrap_or(0);
         let milestone = Milestone {
+            id,
             description,
             amount,
             status: MilestoneStatus::Pending,
             verification_docs: vec![&env],
+            doc_hashes: vec![&env],
             verified_by: None,
+            needs_more_info: false,
+            info_request: None,
+            depends_on,
+            created_at: env.ledger().timestamp(),
+            verified_at: None,
             completed_at: None,
+            nonce: 0,
         };
 
         let mut milestones: Vec<Milestone> = env
@@ -108,9 +399,18 @@ impl VerificationContract {
             .persistent()
             .get(&campaign_id)
             .unwrap_or_else(|| vec![&env]);
+        if milestones.len() >= config.max_milestones {
+            panic_with_error!(&env, VerificationError::TooManyMilestones);
+        }
+        if let Some(dependency) = depends_on {
+            if dependency >= milestones.len() {
+                panic_with_error!(&env, VerificationError::MilestoneNotFound);
+            }
+        }
 
         milestones.push_back(milestone.clone());
         env.storage().persistent().set(&campaign_id, &milestones);
+        env.storage().persistent().set(&id_key, &(id + 1));
         milestone
     }
 
@@ -120,6 +420,7 @@ impl VerificationContract {
         campaign_id: BytesN<32>,
         milestone_index: u32,
         docs: Vec<String>,
+        doc_hashes: Vec<BytesN<32>>,
     ) -> Milestone {
         verifier.require_auth();
 
@@ -138,30 +439,54 @@ impl VerificationContract {
             .get(milestone_index)
             .unwrap_or_else(|| panic_with_error!(&env, VerificationError::MilestoneNotFound));
 
-        if milestone.status != MilestoneStatus::Pending {
-            panic_with_error!(&env, VerificationError::MilestoneNotPending);
-        }
-
-        milestone.status = MilestoneStatus::Verified;
-        milestone.verified_by = Some(verifier);
-        milestone.verification_docs = docs;
+        Self::mark_verified(&env, &config, &mut milestone, verifier, docs, doc_hashes);
 
         milestones.set(milestone_index, milestone.clone());
         env.storage().persistent().set(&campaign_id, &milestones);
         milestone
     }
 
-    pub fn complete_milestone(
+    /// Verifies a milestone with ed25519 signatures from the configured
+    /// verifier set instead of a single verifier's auth. Each signer signs
+    /// the 32 bytes returned by `multisig_digest` for the same campaign,
+    /// milestone and docs. The digest covers the milestone's nonce, so a
+    /// revoked or reverted approval cannot be replayed. `verified_by`
+    /// records the first signer.
+    pub fn verify_milestone_multisig(
         env: Env,
-        verifier: Address,
         campaign_id: BytesN<32>,
         milestone_index: u32,
+        signatures: Vec<(Address, BytesN<64>)>,
+        docs: Vec<String>,
     ) -> Milestone {
-        verifier.require_auth();
-
         let config = Self::read_config(&env, &campaign_id);
-        if config.verifier != verifier {
-            panic_with_error!(&env, VerificationError::Unauthorized);
+        if config.threshold == 0 {
+            panic_with_error!(&env, VerificationError::NotConfigured);
+        }
+
+        let digest: Bytes = Self::multisig_digest(
+            env.clone(),
+            campaign_id.clone(),
+            milestone_index,
+            docs.clone(),
+        )
+        .into();
+        let mut signers: Vec<Address> = vec![&env];
+        for (signer, signature) in signatures.iter() {
+            if signers.contains(&signer) {
+                panic_with_error!(&env, VerificationError::DuplicateSigner);
+            }
+            Self::check_not_blacklisted(&env, &signer);
+            let public_key = config
+                .verifier_keys
+                .get(signer.clone())
+                .unwrap_or_else(|| panic_with_error!(&env, VerificationError::Unauthorized));
+            env.crypto()
+                .ed25519_verify(&public_key, &digest, &signature);
+            signers.push_back(signer);
+        }
+        if signers.len() < config.threshold {
+            panic_with_error!(&env, VerificationError::InsufficientSignatures);
         }
 
         let mut milestones: Vec<Milestone> = env
@@ -169,127 +494,2601 @@ impl VerificationContract {
             .persistent()
             .get(&campaign_id)
             .unwrap_or_else(|| panic_with_error!(&env, VerificationError::MilestoneNotFound));
-
         let mut milestone = milestones
             .get(milestone_index)
             .unwrap_or_else(|| panic_with_error!(&env, VerificationError::MilestoneNotFound));
 
-        if milestone.status != MilestoneStatus::Verified {
-            panic_with_error!(&env, VerificationError::MilestoneNotVerified);
-        }
-
-        let auth_entry = InvokerContractAuthEntry::Contract(SubContractInvocation {
-            context: ContractContext {
-                contract: config.campaign_contract.clone(),
-                fn_name: Symbol::new(&env, "mark_milestone_completed"),
-                args: vec![
-                    &env,
-                    campaign_id.clone().into_val(&env),
-                    milestone.amount.into_val(&env),
-                ],
-            },
-            sub_invocations: vec![&env],
-        });
-        env.authorize_as_current_contract(vec![&env, auth_entry]);
-
-        let campaign_client = CampaignContractClient::new(&env, &config.campaign_contract);
-        campaign_client.mark_milestone_completed(&campaign_id, &milestone.amount);
-
-        milestone.status = MilestoneStatus::Completed;
-        milestone.completed_at = Some(env.ledger().timestamp());
+        Self::mark_verified(
+            &env,
+            &config,
+            &mut milestone,
+            signers.get_unchecked(0),
+            docs,
+            vec![&env],
+        );
 
         milestones.set(milestone_index, milestone.clone());
         env.storage().persistent().set(&campaign_id, &milestones);
         milestone
     }
 
-    pub fn get_milestones(env: Env, campaign_id: BytesN<32>) -> Vec<Milestone> {
-        env.storage()
-            .persistent()
-            .get(&campaign_id)
-            .unwrap_or_else(|| vec![&env])
+    pub fn multisig_digest(
+        env: Env,
+        campaign_id: BytesN<32>,
+        milestone_index: u32,
+        docs: Vec<String>,
+    ) -> BytesN<32> {
+        let milestone = Self::get_milestone(env.clone(), campaign_id.clone(), milestone_index);
+        let mut message = env.current_contract_address().to_xdr(&env);
+        message.extend_from_array(&campaign_id.to_array());
+        message.extend_from_array(&milestone_index.to_be_bytes());
+        message.extend_from_array(&milestone.nonce.to_be_bytes());
+        message.append(&docs.to_xdr(&env));
+        env.crypto().sha256(&message).into()
     }
 
-    pub fn get_milestone(env: Env, campaign_id: BytesN<32>, index: u32) -> Milestone {
-        let milestones: Vec<Milestone> = env
+    pub fn verify_milestones(
+        env: Env,
+        verifier: Address,
+        campaign_id: BytesN<32>,
+        indices: Vec<u32>,
+        docs_per: Vec<Vec<String>>,
+    ) -> Vec<Milestone> {
+        verifier.require_auth();
+
+        let config = Self::read_config(&env, &campaign_id);
+        if config.verifier != verifier {
+            panic_with_error!(&env, VerificationError::Unauthorized);
+        }
+        if docs_per.len() != indices.len() {
+            panic_with_error!(&env, VerificationError::InsufficientDocs);
+        }
+
+        let mut milestones: Vec<Milestone> = env
             .storage()
             .persistent()
             .get(&campaign_id)
             .unwrap_or_else(|| panic_with_error!(&env, VerificationError::MilestoneNotFound));
-        milestones
-            .get(index)
-            .unwrap_or_else(|| panic_with_error!(&env, VerificationError::MilestoneNotFound))
-    }
 
-    pub fn get_config(env: Env, campaign_id: BytesN<32>) -> VerificationConfig {
-        Self::read_config(&env, &campaign_id)
+        let mut verified = vec![&env];
+        for (index, docs) in indices.iter().zip(docs_per.iter()) {
+            let mut milestone = milestones
+                .get(index)
+                .unwrap_or_else(|| panic_with_error!(&env, VerificationError::MilestoneNotFound));
+            Self::mark_verified(
+                &env,
+                &config,
+                &mut milestone,
+                verifier.clone(),
+                docs,
+                vec![&env],
+            );
+            milestones.set(index, milestone.clone());
+            verified.push_back(milestone);
+        }
+
+        env.storage().persistent().set(&campaign_id, &milestones);
+        verified
     }
 
-    fn read_config(env: &Env, campaign_id: &BytesN<32>) -> VerificationConfig {
-        let key = (symbol_short!("cfg"), campaign_id.clone());
-        env.storage()
+    pub fn revoke_verification(
+        env: Env,
+        verifier: Address,
+        campaign_id: BytesN<32>,
+        milestone_index: u32,
+    ) -> Milestone {
+        verifier.require_auth();
+        Self::check_not_blacklisted(&env, &verifier);
+
+        let mut milestones: Vec<Milestone> = env
+            .storage()
             .persistent()
-            .get(&key)
-            .unwrap_or_else(|| panic_with_error!(env, VerificationError::NotConfigured))
-    }
-}
+            .get(&campaign_id)
+            .unwrap_or_else(|| panic_with_error!(&env, VerificationError::MilestoneNotFound));
 
-#[cfg(test)]
-mod test {
-    use super::*;
-    use givehub_campaign::{CampaignContract, CampaignContractClient};
-    use soroban_sdk::{testutils::Address as _, Env, String};
+        let mut milestone = milestones
+            .get(milestone_index)
+            .unwrap_or_else(|| panic_with_error!(&env, VerificationError::MilestoneNotFound));
 
-    #[test]
-    fn test_milestone_lifecycle() {
-        let env = Env::default();
-        env.mock_all_auths();
-        let campaign_addr = env.register_contract(None, CampaignContract);
-        let verification_addr = env.register_contract(None, VerificationContract);
+        if milestone.status != MilestoneStatus::Verified {
+            panic_with_error!(&env, VerificationError::MilestoneNotVerified);
+        }
+        if milestone.verified_by != Some(verifier.clone()) {
+            panic_with_error!(&env, VerificationError::Unauthorized);
+        }
 
-        let campaign_client = CampaignContractClient::new(&env, &campaign_addr);
-        let verification_client = VerificationContractClient::new(&env, &verification_addr);
+        milestone.status = MilestoneStatus::Pending;
+        milestone.verified_by = None;
+        milestone.verified_at = None;
+        milestone.verification_docs = vec![&env];
+        milestone.doc_hashes = vec![&env];
+        milestone.nonce += 1;
 
-        let creator = Address::generate(&env);
-        let verifier = Address::generate(&env);
-        let donation_contract = Address::generate(&env);
-        let campaign_id = BytesN::from_array(&env, &[1; 32]);
+        milestones.set(milestone_index, milestone.clone());
+        env.storage().persistent().set(&campaign_id, &milestones);
 
-        campaign_client.initialize(
-            &creator,
-            &campaign_id,
-            &String::from_str(&env, "Build wells"),
-            &String::from_str(&env, "Provide clean water"),
-            &1000,
+        env.events().publish(
+            (symbol_short!("revoked"), campaign_id),
+            (milestone_index, verifier),
         );
+        milestone
+    }
 
-        campaign_client.set_authorized_contracts(
-            &creator,
-            &campaign_id,
-            &Some(donation_contract.clone()),
-            &Some(verification_addr.clone()),
-        );
+    pub fn request_more_docs(
+        env: Env,
+        verifier: Address,
+        campaign_id: BytesN<32>,
+        milestone_index: u32,
+        message: String,
+    ) -> Milestone {
+        verifier.require_auth();
+        Self::check_not_blacklisted(&env, &verifier);
 
-        verification_client.configure_campaign(&creator, &campaign_addr, &campaign_id, &verifier);
+        let config = Self::read_config(&env, &campaign_id);
+        if config.verifier != verifier {
+            panic_with_error!(&env, VerificationError::Unauthorized);
+        }
+        if message.len() > MAX_CHECKIN_NOTE_LEN {
+            panic_with_error!(&env, VerificationError::NoteTooLong);
+        }
 
-        campaign_client.activate(&creator, &campaign_id);
-        campaign_client.add_donation(&campaign_id, &500);
+        let mut milestones = Self::get_milestones(env.clone(), campaign_id.clone());
+        let mut milestone = milestones
+            .get(milestone_index)
+            .unwrap_or_else(|| panic_with_error!(&env, VerificationError::MilestoneNotFound));
+        if milestone.status != MilestoneStatus::Pending {
+            panic_with_error!(&env, VerificationError::MilestoneNotPending);
+        }
 
-        let milestone = verification_client.create_milestone(
-            &creator,
+        milestone.needs_more_info = true;
+        milestone.info_request = Some(message.clone());
+        milestones.set(milestone_index, milestone.clone());
+        env.storage().persistent().set(&campaign_id, &milestones);
+
+        env.events().publish(
+            (symbol_short!("more_docs"), campaign_id),
+            (milestone_index, message),
+        );
+        milestone
+    }
+
+    pub fn provide_docs(
+        env: Env,
+        owner: Address,
+        campaign_id: BytesN<32>,
+        milestone_index: u32,
+        docs: Vec<String>,
+    ) -> Milestone {
+        let config = Self::read_owned_config(&env, &owner, &campaign_id);
+
+        let mut milestones = Self::get_milestones(env.clone(), campaign_id.clone());
+        let mut milestone = milestones
+            .get(milestone_index)
+            .unwrap_or_else(|| panic_with_error!(&env, VerificationError::MilestoneNotFound));
+        if milestone.status != MilestoneStatus::Pending {
+            panic_with_error!(&env, VerificationError::MilestoneNotPending);
+        }
+        let doc_bytes: u32 = docs.iter().map(|doc| doc.len()).sum();
+        if doc_bytes > config.max_doc_bytes {
+            panic_with_error!(&env, VerificationError::DocsTooLarge);
+        }
+
+        milestone.needs_more_info = false;
+        milestone.info_request = None;
+        milestone.verification_docs = docs;
+        milestones.set(milestone_index, milestone.clone());
+        env.storage().persistent().set(&campaign_id, &milestones);
+        milestone
+    }
+
+    pub fn reject_milestone(
+        env: Env,
+        verifier: Address,
+        campaign_id: BytesN<32>,
+        milestone_index: u32,
+        reason: String,
+    ) -> Milestone {
+        verifier.require_auth();
+        Self::check_not_blacklisted(&env, &verifier);
+
+        let config = Self::read_config(&env, &campaign_id);
+        if config.verifier != verifier {
+            panic_with_error!(&env, VerificationError::Unauthorized);
+        }
+
+        let mut milestones: Vec<Milestone> = env
+            .storage()
+            .persistent()
+            .get(&campaign_id)
+            .unwrap_or_else(|| panic_with_error!(&env, VerificationError::MilestoneNotFound));
+
+        let mut milestone = milestones
+            .get(milestone_index)
+            .unwrap_or_else(|| panic_with_error!(&env, VerificationError::MilestoneNotFound));
+
+        if milestone.status != MilestoneStatus::Pending
+            && milestone.status != MilestoneStatus::Verified
+        {
+            panic_with_error!(&env, VerificationError::MilestoneNotPending);
+        }
+
+        milestone.status = MilestoneStatus::Failed;
+        milestones.set(milestone_index, milestone.clone());
+        env.storage().persistent().set(&campaign_id, &milestones);
+
+        env.events().publish(
+            (symbol_short!("rejected"), campaign_id.clone()),
+            (milestone_index, reason),
+        );
+
+        let failed = Self::failed_milestones(env.clone(), campaign_id.clone());
+        let campaign_client = CampaignContractClient::new(&env, &config.campaign_contract);
+        if config.failure_threshold > 0
+            && failed > config.failure_threshold
+            && matches!(
+                Self::campaign_call(&env, campaign_client.try_status(&campaign_id)),
+                CampaignStatus::Draft | CampaignStatus::Active | CampaignStatus::Funded
+            )
+        {
+            Self::campaign_call(
+                &env,
+                campaign_client.try_cancel_by_verification(&campaign_id),
+            );
+            env.events().publish(
+                (symbol_short!("tripwire"), campaign_id),
+                (failed, config.failure_threshold),
+            );
+        }
+        milestone
+    }
+
+    pub fn failed_milestones(env: Env, campaign_id: BytesN<32>) -> u32 {
+        Self::get_milestones(env, campaign_id)
+            .iter()
+            .filter(|milestone| milestone.status == MilestoneStatus::Failed)
+            .count() as u32
+    }
+
+    pub fn checkin(env: Env, verifier: Address, campaign_id: BytesN<32>, note: String) -> CheckIn {
+        verifier.require_auth();
+        Self::check_not_blacklisted(&env, &verifier);
+
+        let config = Self::read_config(&env, &campaign_id);
+        if config.verifier != verifier {
+            panic_with_error!(&env, VerificationError::Unauthorized);
+        }
+        if note.len() > MAX_CHECKIN_NOTE_LEN {
+            panic_with_error!(&env, VerificationError::NoteTooLong);
+        }
+
+        let checkin = CheckIn {
+            verifier,
+            note,
+            timestamp: env.ledger().timestamp(),
+        };
+        let mut checkins = Self::get_checkins(env.clone(), campaign_id.clone());
+        checkins.push_back(checkin.clone());
+        env.storage()
+            .persistent()
+            .set(&(symbol_short!("checkins"), campaign_id.clone()), &checkins);
+
+        env.events()
+            .publish((symbol_short!("checkin"), campaign_id), checkin.clone());
+        checkin
+    }
+
+    pub fn get_checkins(env: Env, campaign_id: BytesN<32>) -> Vec<CheckIn> {
+        env.storage()
+            .persistent()
+            .get(&(symbol_short!("checkins"), campaign_id))
+            .unwrap_or_else(|| vec![&env])
+    }
+
+    pub fn revert_stale_verification(
+        env: Env,
+        campaign_id: BytesN<32>,
+        milestone_index: u32,
+    ) -> Milestone {
+        let config = Self::read_config(&env, &campaign_id);
+        let mut milestones = Self::get_milestones(env.clone(), campaign_id.clone());
+        let mut milestone = milestones
+            .get(milestone_index)
+            .unwrap_or_else(|| panic_with_error!(&env, VerificationError::MilestoneNotFound));
+        if milestone.status != MilestoneStatus::Verified {
+            panic_with_error!(&env, VerificationError::MilestoneNotVerified);
+        }
+        if !Self::approval_stale(&env, &config, &milestone) {
+            panic_with_error!(&env, VerificationError::ApprovalNotStale);
+        }
+
+        milestone.status = MilestoneStatus::Pending;
+        milestone.verified_by = None;
+        milestone.verified_at = None;
+        milestone.verification_docs = vec![&env];
+        milestone.doc_hashes = vec![&env];
+        milestone.nonce += 1;
+        milestones.set(milestone_index, milestone.clone());
+        env.storage().persistent().set(&campaign_id, &milestones);
+        milestone
+    }
+
+    pub fn complete_milestone(
+        env: Env,
+        verifier: Address,
+        campaign_id: BytesN<32>,
+        milestone_index: u32,
+    ) -> Milestone {
+        verifier.require_auth();
+
+        let config = Self::read_config(&env, &campaign_id);
+        if config.verifier != verifier {
+            panic_with_error!(&env, VerificationError::Unauthorized);
+        }
+
+        let mut milestones: Vec<Milestone> = env
+            .storage()
+            .persistent()
+            .get(&campaign_id)
+            .unwrap_or_else(|| vec![&env]);
+        if milestones.is_empty() {
+            panic_with_error!(&env, VerificationError::NoMilestones);
+        }
+        if milestones.len() < config.min_milestones {
+            panic_with_error!(&env, VerificationError::TooFewMilestones);
+        }
+
+        let mut milestone = milestones
+            .get(milestone_index)
+            .unwrap_or_else(|| panic_with_error!(&env, VerificationError::MilestoneNotFound));
+
+        Self::check_dependency(&env, &milestones, &milestone);
+        Self::mark_completed(&env, &config, &campaign_id, milestone_index, &mut milestone);
+
+        milestones.set(milestone_index, milestone.clone());
+        env.storage().persistent().set(&campaign_id, &milestones);
+        milestone
+    }
+
+    pub fn verify_and_complete(
+        env: Env,
+        verifier: Address,
+        campaign_id: BytesN<32>,
+        milestone_index: u32,
+        docs: Vec<String>,
+        doc_hashes: Vec<BytesN<32>>,
+    ) -> Milestone {
+        verifier.require_auth();
+
+        let config = Self::read_config(&env, &campaign_id);
+        if config.verifier != verifier {
+            panic_with_error!(&env, VerificationError::Unauthorized);
+        }
+
+        let mut milestones: Vec<Milestone> = env
+            .storage()
+            .persistent()
+            .get(&campaign_id)
+            .unwrap_or_else(|| vec![&env]);
+        if milestones.is_empty() {
+            panic_with_error!(&env, VerificationError::NoMilestones);
+        }
+        if milestones.len() < config.min_milestones {
+            panic_with_error!(&env, VerificationError::TooFewMilestones);
+        }
+
+        let mut milestone = milestones
+            .get(milestone_index)
+            .unwrap_or_else(|| panic_with_error!(&env, VerificationError::MilestoneNotFound));
+
+        Self::mark_verified(&env, &config, &mut milestone, verifier, docs, doc_hashes);
+        Self::check_dependency(&env, &milestones, &milestone);
+        Self::mark_completed(&env, &config, &campaign_id, milestone_index, &mut milestone);
+
+        milestones.set(milestone_index, milestone.clone());
+        env.storage().persistent().set(&campaign_id, &milestones);
+        milestone
+    }
+
+    pub fn cleanup(env: Env, owner: Address, campaign_id: BytesN<32>) {
+        let config = Self::read_owned_config(&env, &owner, &campaign_id);
+
+        let campaign_client = CampaignContractClient::new(&env, &config.campaign_contract);
+        let status = Self::campaign_call(&env, campaign_client.try_status(&campaign_id));
+        if !matches!(
+            status,
+            CampaignStatus::Cancelled | CampaignStatus::Completed | CampaignStatus::Expired
+        ) {
+            panic_with_error!(&env, VerificationError::CampaignNotFinished);
+        }
+
+        env.storage()
+            .persistent()
+            .remove(&(symbol_short!("cfg"), campaign_id.clone()));
+        env.storage()
+            .persistent()
+            .remove(&(symbol_short!("cfg_v2"), campaign_id.clone()));
+        env.storage().persistent().remove(&campaign_id);
+        env.storage()
+            .persistent()
+            .remove(&(symbol_short!("checkins"), campaign_id.clone()));
+        env.storage().persistent().set(
+            &(symbol_short!("tomb"), campaign_id),
+            &Tombstone {
+                purged_at: env.ledger().timestamp(),
+                reason: status,
+            },
+        );
+    }
+
+    pub fn get_tombstone(env: Env, campaign_id: BytesN<32>) -> Option<Tombstone> {
+        env.storage()
+            .persistent()
+            .get(&(symbol_short!("tomb"), campaign_id))
+    }
+
+    pub fn is_milestone_funded(env: Env, campaign_id: BytesN<32>, milestone_index: u32) -> bool {
+        let milestone = Self::get_milestone(env.clone(), campaign_id.clone(), milestone_index);
+        let config = Self::read_config(&env, &campaign_id);
+
+        let campaign_client = CampaignContractClient::new(&env, &config.campaign_contract);
+        campaign_client.available_funds(&campaign_id) >= milestone.amount
+    }
+
+    pub fn coverage_ratio(env: Env, campaign_id: BytesN<32>) -> u32 {
+        let config = Self::read_config(&env, &campaign_id);
+        let outstanding: i128 = Self::get_milestones(env.clone(), campaign_id.clone())
+            .iter()
+            .filter(|milestone| {
+                matches!(
+                    milestone.status,
+                    MilestoneStatus::Pending | MilestoneStatus::Verified
+                )
+            })
+            .map(|milestone| milestone.amount)
+            .sum();
+        if outstanding == 0 {
+            return MAX_BPS;
+        }
+
+        let campaign_client = CampaignContractClient::new(&env, &config.campaign_contract);
+        let available = campaign_client.available_funds(&campaign_id).max(0);
+        (available * MAX_BPS as i128 / outstanding).min(MAX_BPS as i128) as u32
+    }
+
+    pub fn next_payable_milestone(env: Env, campaign_id: BytesN<32>) -> Option<u32> {
+        let config = Self::read_config(&env, &campaign_id);
+        let milestones = Self::get_milestones(env.clone(), campaign_id.clone());
+        if milestones.len() < config.min_milestones {
+            return None;
+        }
+
+        if Self::is_verifier_blacklisted(env.clone(), config.verifier.clone()) {
+            return None;
+        }
+
+        let campaign_client = CampaignContractClient::new(&env, &config.campaign_contract);
+        if config.require_funded_to_complete
+            && !matches!(
+                campaign_client.status(&campaign_id),
+                CampaignStatus::Funded | CampaignStatus::Completed
+            )
+        {
+            return None;
+        }
+        let available = campaign_client.available_funds(&campaign_id);
+        for (index, milestone) in milestones.iter().enumerate() {
+            let approver_blacklisted = milestone
+                .verified_by
+                .clone()
+                .is_some_and(|approver| Self::is_verifier_blacklisted(env.clone(), approver));
+            if milestone.status != MilestoneStatus::Verified
+                || approver_blacklisted
+                || Self::approval_stale(&env, &config, &milestone)
+                || Self::docs_expired(&env, &config, &milestone)
+                || !Self::dependency_met(&milestones, &milestone)
+                || available < milestone.amount
+            {
+                continue;
+            }
+            return Some(index as u32);
+        }
+        None
+    }
+
+    pub fn simulate_complete(
+        env: Env,
+        campaign_id: BytesN<32>,
+        milestone_index: u32,
+    ) -> (i128, CampaignStatus) {
+        let milestone = Self::get_milestone(env.clone(), campaign_id.clone(), milestone_index);
+        let config = Self::read_config(&env, &campaign_id);
+
+        let campaign_client = CampaignContractClient::new(&env, &config.campaign_contract);
+        let campaign = campaign_client.get(&campaign_id);
+        if milestone.amount == 0 {
+            return (campaign.released_amount, campaign.status);
+        }
+
+        let new_released = campaign.released_amount + milestone.amount;
+        let projected_status = if new_released >= campaign.target_amount {
+            CampaignStatus::Completed
+        } else {
+            campaign.status
+        };
+        (new_released, projected_status)
+    }
+
+    pub fn get_milestones(env: Env, campaign_id: BytesN<32>) -> Vec<Milestone> {
+        env.storage()
+            .persistent()
+            .get(&campaign_id)
+            .unwrap_or_else(|| {
+                Self::check_not_purged(&env, &campaign_id);
+                vec![&env]
+            })
+    }
+
+    /// The sha256 of the XDR encoding of the campaign's milestones, which
+    /// the campaign contract folds into its export bundle.
+    pub fn milestones_digest(env: Env, campaign_id: BytesN<32>) -> BytesN<32> {
+        let milestones = Self::get_milestones(env.clone(), campaign_id);
+        env.crypto().sha256(&milestones.to_xdr(&env)).into()
+    }
+
+    pub fn get_milestones_by_status(
+        env: Env,
+        campaign_id: BytesN<32>,
+        status: MilestoneStatus,
+    ) -> Vec<(u32, Milestone)> {
+        let mut matching = vec![&env];
+        for (index, milestone) in Self::get_milestones(env.clone(), campaign_id)
+            .iter()
+            .enumerate()
+        {
+            if milestone.status == status {
+                matching.push_back((index as u32, milestone));
+            }
+        }
+        matching
+    }
+
+    pub fn get_milestone(env: Env, campaign_id: BytesN<32>, index: u32) -> Milestone {
+        let milestones: Vec<Milestone> = env
+            .storage()
+            .persistent()
+            .get(&campaign_id)
+            .unwrap_or_else(|| {
+                Self::check_not_purged(&env, &campaign_id);
+                panic_with_error!(&env, VerificationError::MilestoneNotFound)
+            });
+        milestones
+            .get(index)
+            .unwrap_or_else(|| panic_with_error!(&env, VerificationError::MilestoneNotFound))
+    }
+
+    pub fn milestone_index(env: Env, campaign_id: BytesN<32>, milestone_id: u32) -> u32 {
+        Self::get_milestones(env.clone(), campaign_id)
+            .iter()
+            .position(|milestone| milestone.id == milestone_id)
+            .unwrap_or_else(|| panic_with_error!(&env, VerificationError::MilestoneNotFound))
+            as u32
+    }
+
+    pub fn get_milestone_by_id(env: Env, campaign_id: BytesN<32>, milestone_id: u32) -> Milestone {
+        let index = Self::milestone_index(env.clone(), campaign_id.clone(), milestone_id);
+        Self::get_milestone(env, campaign_id, index)
+    }
+
+    pub fn verify_milestone_by_id(
+        env: Env,
+        verifier: Address,
+        campaign_id: BytesN<32>,
+        milestone_id: u32,
+        docs: Vec<String>,
+        doc_hashes: Vec<BytesN<32>>,
+    ) -> Milestone {
+        let index = Self::milestone_index(env.clone(), campaign_id.clone(), milestone_id);
+        Self::verify_milestone(env, verifier, campaign_id, index, docs, doc_hashes)
+    }
+
+    pub fn complete_milestone_by_id(
+        env: Env,
+        verifier: Address,
+        campaign_id: BytesN<32>,
+        milestone_id: u32,
+    ) -> Milestone {
+        let index = Self::milestone_index(env.clone(), campaign_id.clone(), milestone_id);
+        Self::complete_milestone(env, verifier, campaign_id, index)
+    }
+
+    pub fn milestone_durations(env: Env, campaign_id: BytesN<32>, index: u32) -> (u64, u64) {
+        let milestone = Self::get_milestone(env.clone(), campaign_id, index);
+        let now = env.ledger().timestamp();
+
+        match milestone.verified_at {
+            Some(verified_at) => (
+                verified_at - milestone.created_at,
+                milestone.completed_at.unwrap_or(now) - verified_at,
+            ),
+            None => (now - milestone.created_at, 0),
+        }
+    }
+
+    pub fn verify_doc(
+        env: Env,
+        campaign_id: BytesN<32>,
+        milestone_index: u32,
+        content: Bytes,
+    ) -> bool {
+        let milestone = Self::get_milestone(env.clone(), campaign_id, milestone_index);
+        let hash: BytesN<32> = env.crypto().sha256(&content).into();
+        milestone.doc_hashes.contains(&hash)
+    }
+
+    pub fn get_config(env: Env, campaign_id: BytesN<32>) -> VerificationConfig {
+        Self::read_config(&env, &campaign_id)
+    }
+
+    fn campaign_call<T, E, F>(env: &Env, result: Result<Result<T, E>, F>) -> T {
+        match result {
+            Ok(Ok(value)) => value,
+            _ => panic_with_error!(env, VerificationError::CampaignCallFailed),
+        }
+    }
+
+    fn check_not_blacklisted(env: &Env, verifier: &Address) {
+        if Self::is_verifier_blacklisted(env.clone(), verifier.clone()) {
+            panic_with_error!(env, VerificationError::VerifierBlacklisted);
+        }
+    }
+
+    fn mark_verified(
+        env: &Env,
+        config: &VerificationConfig,
+        milestone: &mut Milestone,
+        verifier: Address,
+        docs: Vec<String>,
+        doc_hashes: Vec<BytesN<32>>,
+    ) {
+        Self::check_not_blacklisted(env, &verifier);
+        if milestone.status != MilestoneStatus::Pending {
+            panic_with_error!(env, VerificationError::MilestoneNotPending);
+        }
+        if docs.len() < config.min_docs {
+            panic_with_error!(env, VerificationError::InsufficientDocs);
+        }
+        let doc_bytes: u32 = docs.iter().map(|doc| doc.len()).sum();
+        let hash_bytes = doc_hashes.len().saturating_mul(32);
+        if doc_bytes.saturating_add(hash_bytes) > config.max_doc_bytes {
+            panic_with_error!(env, VerificationError::DocsTooLarge);
+        }
+
+        milestone.status = MilestoneStatus::Verified;
+        milestone.needs_more_info = false;
+        milestone.info_request = None;
+        milestone.verified_by = Some(verifier);
+        milestone.verified_at = Some(env.ledger().timestamp());
+        milestone.verification_docs = docs;
+        milestone.doc_hashes = doc_hashes;
+    }
+
+    fn approval_stale(env: &Env, config: &VerificationConfig, milestone: &Milestone) -> bool {
+        match (config.approval_ttl_secs, milestone.verified_at) {
+            (Some(ttl), Some(verified_at)) => {
+                verified_at.saturating_add(ttl) < env.ledger().timestamp()
+            }
+            _ => false,
+        }
+    }
+
+    fn docs_expired(env: &Env, config: &VerificationConfig, milestone: &Milestone) -> bool {
+        match (config.docs_valid_secs, milestone.verified_at) {
+            (Some(valid_secs), Some(verified_at)) => {
+                verified_at.saturating_add(valid_secs) < env.ledger().timestamp()
+            }
+            _ => false,
+        }
+    }
+
+    fn check_dependency(env: &Env, milestones: &Vec<Milestone>, milestone: &Milestone) {
+        if !Self::dependency_met(milestones, milestone) {
+            panic_with_error!(env, VerificationError::DependencyNotMet);
+        }
+    }
+
+    fn dependency_met(milestones: &Vec<Milestone>, milestone: &Milestone) -> bool {
+        milestone.depends_on.is_none_or(|dependency| {
+            milestones
+                .get(dependency)
+                .is_some_and(|dependency| dependency.status == MilestoneStatus::Completed)
+        })
+    }
+
+    fn mark_completed(
+        env: &Env,
+        config: &VerificationConfig,
+        campaign_id: &BytesN<32>,
+        milestone_index: u32,
+        milestone: &mut Milestone,
+    ) {
+        Self::check_not_blacklisted(env, &config.verifier);
+        if let Some(ref approver) = milestone.verified_by {
+            Self::check_not_blacklisted(env, approver);
+        }
+        if milestone.status != MilestoneStatus::Verified
+            || Self::approval_stale(env, config, milestone)
+        {
+            panic_with_error!(env, VerificationError::MilestoneNotVerified);
+        }
+        if Self::docs_expired(env, config, milestone) {
+            panic_with_error!(env, VerificationError::DocsExpired);
+        }
+
+        let campaign_client = CampaignContractClient::new(env, &config.campaign_contract);
+        if config.require_funded_to_complete
+            && !matches!(
+                Self::campaign_call(env, campaign_client.try_status(campaign_id)),
+                CampaignStatus::Funded | CampaignStatus::Completed
+            )
+        {
+            panic_with_error!(env, VerificationError::CampaignNotFunded);
+        }
+
+        if milestone.amount > 0 {
+            let available =
+                Self::campaign_call(env, campaign_client.try_available_funds(campaign_id));
+            if available < milestone.amount {
+                panic_with_error!(env, VerificationError::InsufficientFunds);
+            }
+
+            let auth_entry = Self::release_auth_entry(
+                env,
+                &config.campaign_contract,
+                campaign_id,
+                milestone.amount,
+            );
+            env.authorize_as_current_contract(vec![env, auth_entry]);
+            Self::campaign_call(
+                env,
+                campaign_client.try_mark_milestone_completed(campaign_id, &milestone.amount),
+            );
+
+            // Earmark bookkeeping is best effort, like the campaign's release
+            // listener: a donation contract without earmarks must not block
+            // the release itself.
+            let donation_contract =
+                Self::campaign_call(env, campaign_client.try_donation_contract(campaign_id));
+            if let Some(donation_contract) = donation_contract {
+                let _ = env.try_invoke_contract::<i128, InvokeError>(
+                    &donation_contract,
+                    &Symbol::new(env, "draw_earmark"),
+                    (
+                        config.campaign_contract.clone(),
+                        campaign_id.clone(),
+                        milestone_index,
+                        milestone.amount,
+                    )
+                        .into_val(env),
+                );
+            }
+        }
+
+        milestone.status = MilestoneStatus::Completed;
+        milestone.completed_at = Some(env.ledger().timestamp());
+    }
+
+    fn release_auth_entry(
+        env: &Env,
+        campaign_contract: &Address,
+        campaign_id: &BytesN<32>,
+        amount: i128,
+    ) -> InvokerContractAuthEntry {
+        InvokerContractAuthEntry::Contract(SubContractInvocation {
+            context: ContractContext {
+                contract: campaign_contract.clone(),
+                fn_name: Symbol::new(env, "mark_milestone_completed"),
+                args: (campaign_id.clone(), amount).into_val(env),
+            },
+            sub_invocations: vec![env],
+        })
+    }
+
+    pub fn describe_error(_env: Env, code: u32) -> Symbol {
+        match VerificationError::try_from(Error::from_contract_error(code)) {
+            Ok(error) => error.symbol(),
+            Err(_) => symbol_short!("unknown"),
+        }
+    }
+
+    fn require_admin(env: &Env, admin: &Address) {
+        givehub_admin::require_admin::<VerificationError>(env, admin);
+    }
+
+    fn read_owned_config(
+        env: &Env,
+        owner: &Address,
+        campaign_id: &BytesN<32>,
+    ) -> VerificationConfig {
+        owner.require_auth();
+        let config = Self::read_config(env, campaign_id);
+        if config.owner != *owner {
+            panic_with_error!(env, VerificationError::Unauthorized);
+        }
+        config
+    }
+
+    fn read_config(env: &Env, campaign_id: &BytesN<32>) -> VerificationConfig {
+        let storage = env.storage().persistent();
+        if let Some(config) = storage.get(&(symbol_short!("cfg_v2"), campaign_id.clone())) {
+            return config;
+        }
+        if let Some(legacy) =
+            storage.get::<_, VerificationConfigV1>(&(symbol_short!("cfg"), campaign_id.clone()))
+        {
+            return Self::default_config(
+                env,
+                legacy.campaign_contract,
+                legacy.owner,
+                legacy.verifier,
+                DEFAULT_MAX_MILESTONES,
+            );
+        }
+        Self::check_not_purged(env, campaign_id);
+        panic_with_error!(env, VerificationError::NotConfigured)
+    }
+
+    fn default_config(
+        env: &Env,
+        campaign_contract: Address,
+        owner: Address,
+        verifier: Address,
+        max_milestones: u32,
+    ) -> VerificationConfig {
+        VerificationConfig {
+            campaign_contract,
+            owner,
+            verifier,
+            max_milestones,
+            max_doc_bytes: DEFAULT_MAX_DOC_BYTES,
+            min_docs: DEFAULT_MIN_DOCS,
+            min_milestones: DEFAULT_MIN_MILESTONES,
+            docs_valid_secs: None,
+            verifier_keys: Map::new(env),
+            threshold: 0,
+            require_funded_to_complete: false,
+            failure_threshold: 0,
+            approval_ttl_secs: None,
+        }
+    }
+
+    fn check_not_purged(env: &Env, campaign_id: &BytesN<32>) {
+        if env
+            .storage()
+            .persistent()
+            .has(&(symbol_short!("tomb"), campaign_id.clone()))
+        {
+            panic_with_error!(env, VerificationError::Purged);
+        }
+    }
+
+    fn save_config(env: &Env, campaign_id: &BytesN<32>, config: &VerificationConfig) {
+        let storage = env.storage().persistent();
+        storage.set(&(symbol_short!("cfg_v2"), campaign_id.clone()), config);
+        storage.remove(&(symbol_short!("cfg"), campaign_id.clone()));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use givehub_admin::{test_deployed_address, test_salt};
+    use givehub_campaign::{CampaignContract, CampaignContractClient};
+    use soroban_sdk::{
+        testutils::{Address as _, Ledger, MockAuth, MockAuthInvoke},
+        Env, String, Val,
+    };
+
+    #[test]
+    fn test_milestone_lifecycle() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let campaign_addr = env.register_contract(None, CampaignContract);
+        let verification_addr = env.register_contract(None, VerificationContract);
+
+        let campaign_client = CampaignContractClient::new(&env, &campaign_addr);
+        let verification_client = VerificationContractClient::new(&env, &verification_addr);
+
+        let creator = Address::generate(&env);
+        let verifier = Address::generate(&env);
+        let donation_contract = Address::generate(&env);
+        let token = env
+            .register_stellar_asset_contract_v2(Address::generate(&env))
+            .address();
+        let campaign_id = BytesN::from_array(&env, &[1; 32]);
+
+        campaign_client.initialize(
+            &creator,
+            &campaign_id,
+            &String::from_str(&env, "Build wells"),
+            &String::from_str(&env, "Provide clean water"),
+            &1000,
+            &Some(token.clone()),
+            &false,
+        );
+
+        campaign_client.set_authorized_contracts(
+            &creator,
+            &campaign_id,
+            &Some(donation_contract.clone()),
+            &Some(verification_addr.clone()),
+        );
+
+        verification_client.configure_campaign(
+            &creator,
+            &campaign_addr,
+            &campaign_id,
+            &verifier,
+            &None,
+        );
+
+        campaign_client.activate(&creator, &campaign_id);
+        campaign_client.add_donation(&campaign_id, &token, &500);
+
+        let milestone = verification_client.create_milestone(
+            &creator,
             &campaign_id,
             &String::from_str(&env, "Drill first well"),
             &400,
+            &None,
+        );
+
+        assert_eq!(milestone.status, MilestoneStatus::Pending);
+
+        let docs = vec![&env, String::from_str(&env, "report.pdf")];
+        let verified =
+            verification_client.verify_milestone(&verifier, &campaign_id, &0, &docs, &vec![&env]);
+        assert_eq!(verified.status, MilestoneStatus::Verified);
+
+        let completed = verification_client.complete_milestone(&verifier, &campaign_id, &0);
+        assert_eq!(completed.status, MilestoneStatus::Completed);
+        assert!(completed.completed_at.is_some());
+    }
+
+    struct Setup<'a> {
+        env: Env,
+        campaign: CampaignContractClient<'a>,
+        verification: VerificationContractClient<'a>,
+        deployer: Address,
+        creator: Address,
+        verifier: Address,
+        token: Address,
+        campaign_id: BytesN<32>,
+    }
+
+    impl Setup<'_> {
+        fn init_admin(&self, admin: &Address) {
+            self.verification
+                .init_admin(&self.deployer, &test_salt(&self.env, 0), admin);
+        }
+    }
+
+    fn setup<'a>(target: i128) -> Setup<'a> {
+        let env = Env::default();
+        env.mock_all_auths();
+        let deployer = Address::generate(&env);
+        let campaign_addr = env.register_contract(None, CampaignContract);
+        let verification_addr = env.register_contract(
+            Some(&test_deployed_address(&env, &deployer, 0)),
+            VerificationContract,
+        );
+
+        let campaign = CampaignContractClient::new(&env, &campaign_addr);
+        let verification = VerificationContractClient::new(&env, &verification_addr);
+
+        let creator = Address::generate(&env);
+        let verifier = Address::generate(&env);
+        let donation_contract = Address::generate(&env);
+        let token = env
+            .register_stellar_asset_contract_v2(Address::generate(&env))
+            .address();
+        let campaign_id = BytesN::from_array(&env, &[1; 32]);
+
+        campaign.initialize(
+            &creator,
+            &campaign_id,
+            &String::from_str(&env, "Build wells"),
+            &String::from_str(&env, "Provide clean water"),
+            &target,
+            &Some(token.clone()),
+            &false,
+        );
+        campaign.set_authorized_contracts(
+            &creator,
+            &campaign_id,
+            &Some(donation_contract),
+            &Some(verification_addr),
+        );
+        verification.configure_campaign(
+            &creator,
+            &campaign_addr,
+            &campaign_id,
+            &verifier,
+            &Some(3),
+        );
+        campaign.activate(&creator, &campaign_id);
+
+        Setup {
+            env,
+            campaign,
+            verification,
+            deployer,
+            creator,
+            verifier,
+            token,
+            campaign_id,
+        }
+    }
+
+    #[test]
+    fn test_simulate_complete() {
+        let s = setup(1000);
+        s.campaign.add_donation(&s.campaign_id, &s.token, &1000);
+        for amount in [400, 600] {
+            s.verification.create_milestone(
+                &s.creator,
+                &s.campaign_id,
+                &String::from_str(&s.env, "Drill a well"),
+                &amount,
+                &None,
+            );
+        }
+
+        let (released, status) = s.verification.simulate_complete(&s.campaign_id, &0);
+        assert_eq!(released, 400);
+        assert_eq!(status, CampaignStatus::Funded);
+        assert_eq!(s.campaign.get(&s.campaign_id).released_amount, 0);
+
+        let docs = vec![&s.env, String::from_str(&s.env, "report.pdf")];
+        s.verification
+            .verify_milestone(&s.verifier, &s.campaign_id, &0, &docs, &vec![&s.env]);
+        s.verification
+            .complete_milestone(&s.verifier, &s.campaign_id, &0);
+
+        let (released, status) = s.verification.simulate_complete(&s.campaign_id, &1);
+        assert_eq!(released, 1000);
+        assert_eq!(status, CampaignStatus::Completed);
+        assert_eq!(
+            s.verification.try_simulate_complete(&s.campaign_id, &2),
+            Err(Ok(VerificationError::MilestoneNotFound.into()))
+        );
+    }
+
+    #[test]
+    fn test_max_milestones() {
+        let s = setup(1000);
+        let description = String::from_str(&s.env, "Drill a well");
+        for _ in 0..3 {
+            s.verification
+                .create_milestone(&s.creator, &s.campaign_id, &description, &100, &None);
+        }
+        assert_eq!(
+            s.verification.try_create_milestone(
+                &s.creator,
+                &s.campaign_id,
+                &description,
+                &100,
+                &None
+            ),
+            Err(Ok(VerificationError::TooManyMilestones.into()))
+        );
+        assert_eq!(s.verification.get_milestones(&s.campaign_id).len(), 3);
+
+        for invalid in [0, MAX_MILESTONES_LIMIT + 1, u32::MAX] {
+            assert_eq!(
+                s.verification.try_configure_campaign(
+                    &s.creator,
+                    &s.campaign.address,
+                    &s.campaign_id,
+                    &s.verifier,
+                    &Some(invalid),
+                ),
+                Err(Ok(VerificationError::InvalidMaxMilestones.into()))
+            );
+        }
+        let config = s.verification.configure_campaign(
+            &s.creator,
+            &s.campaign.address,
+            &s.campaign_id,
+            &s.verifier,
+            &Some(MAX_MILESTONES_LIMIT),
+        );
+        assert_eq!(config.max_milestones, MAX_MILESTONES_LIMIT);
+    }
+
+    #[test]
+    fn test_revoke_verification() {
+        let s = setup(1000);
+        s.campaign.add_donation(&s.campaign_id, &s.token, &500);
+        s.verification.create_milestone(
+            &s.creator,
+            &s.campaign_id,
+            &String::from_str(&s.env, "Drill a well"),
+            &400,
+            &None,
+        );
+
+        assert_eq!(
+            s.verification
+                .try_revoke_verification(&s.verifier, &s.campaign_id, &0),
+            Err(Ok(VerificationError::MilestoneNotVerified.into()))
+        );
+
+        let docs = vec![&s.env, String::from_str(&s.env, "report.pdf")];
+        s.verification
+            .verify_milestone(&s.verifier, &s.campaign_id, &0, &docs, &vec![&s.env]);
+
+        let other = Address::generate(&s.env);
+        assert_eq!(
+            s.verification
+                .try_revoke_verification(&other, &s.campaign_id, &0),
+            Err(Ok(VerificationError::Unauthorized.into()))
+        );
+
+        let revoked = s
+            .verification
+            .revoke_verification(&s.verifier, &s.campaign_id, &0);
+        assert_eq!(revoked.status, MilestoneStatus::Pending);
+        assert_eq!(revoked.verified_by, None);
+        assert_eq!(revoked.verification_docs.len(), 0);
+        assert_eq!(s.verification.get_milestone(&s.campaign_id, &0), revoked);
+        assert_eq!(
+            s.verification
+                .try_complete_milestone(&s.verifier, &s.campaign_id, &0),
+            Err(Ok(VerificationError::MilestoneNotVerified.into()))
+        );
+    }
+
+    #[test]
+    fn test_coverage_ratio() {
+        let s = setup(1000);
+        assert_eq!(s.verification.coverage_ratio(&s.campaign_id), MAX_BPS);
+
+        let description = String::from_str(&s.env, "Drill a well");
+        s.verification
+            .create_milestone(&s.creator, &s.campaign_id, &description, &400, &None);
+        s.verification
+            .create_milestone(&s.creator, &s.campaign_id, &description, &400, &None);
+        assert_eq!(s.verification.coverage_ratio(&s.campaign_id), 0);
+
+        s.campaign.add_donation(&s.campaign_id, &s.token, &600);
+        assert_eq!(s.verification.coverage_ratio(&s.campaign_id), 7_500);
+
+        let docs = vec![&s.env, String::from_str(&s.env, "report.pdf")];
+        s.verification
+            .verify_milestone(&s.verifier, &s.campaign_id, &0, &docs, &vec![&s.env]);
+        s.verification
+            .complete_milestone(&s.verifier, &s.campaign_id, &0);
+        assert_eq!(s.verification.coverage_ratio(&s.campaign_id), 5_000);
+
+        s.campaign.add_donation(&s.campaign_id, &s.token, &400);
+        assert_eq!(s.verification.coverage_ratio(&s.campaign_id), MAX_BPS);
+    }
+
+    #[test]
+    fn test_next_payable_milestone() {
+        let s = setup(1000);
+        s.campaign.add_donation(&s.campaign_id, &s.token, &300);
+        let description = String::from_str(&s.env, "Drill a well");
+        s.verification
+            .create_milestone(&s.creator, &s.campaign_id, &description, &200, &None);
+        s.verification
+            .create_milestone(&s.creator, &s.campaign_id, &description, &500, &None);
+        s.verification
+            .create_milestone(&s.creator, &s.campaign_id, &description, &100, &Some(0));
+        assert_eq!(s.verification.next_payable_milestone(&s.campaign_id), None);
+
+        let docs = vec![&s.env, String::from_str(&s.env, "report.pdf")];
+        for index in 0..3 {
+            s.verification.verify_milestone(
+                &s.verifier,
+                &s.campaign_id,
+                &index,
+                &docs,
+                &vec![&s.env],
+            );
+        }
+        assert_eq!(
+            s.verification.next_payable_milestone(&s.campaign_id),
+            Some(0)
+        );
+
+        s.verification
+            .complete_milestone(&s.verifier, &s.campaign_id, &0);
+        assert_eq!(
+            s.verification.next_payable_milestone(&s.campaign_id),
+            Some(2)
+        );
+
+        s.verification
+            .complete_milestone(&s.verifier, &s.campaign_id, &2);
+        assert_eq!(s.verification.next_payable_milestone(&s.campaign_id), None);
+    }
+
+    #[test]
+    fn test_milestone_ids() {
+        let s = setup(1000);
+        s.campaign.add_donation(&s.campaign_id, &s.token, &500);
+        let description = String::from_str(&s.env, "Drill a well");
+        for expected in 0..2 {
+            let milestone = s.verification.create_milestone(
+                &s.creator,
+                &s.campaign_id,
+                &description,
+                &200,
+                &None,
+            );
+            assert_eq!(milestone.id, expected);
+        }
+        assert_eq!(s.verification.milestone_index(&s.campaign_id, &1), 1);
+        assert_eq!(
+            s.verification.try_milestone_index(&s.campaign_id, &2),
+            Err(Ok(VerificationError::MilestoneNotFound.into()))
+        );
+
+        let docs = vec![&s.env, String::from_str(&s.env, "report.pdf")];
+        s.verification.verify_milestone_by_id(
+            &s.verifier,
+            &s.campaign_id,
+            &1,
+            &docs,
+            &vec![&s.env],
+        );
+        let completed = s
+            .verification
+            .complete_milestone_by_id(&s.verifier, &s.campaign_id, &1);
+        assert_eq!(completed.id, 1);
+        assert_eq!(completed.status, MilestoneStatus::Completed);
+        assert_eq!(
+            s.verification.get_milestone_by_id(&s.campaign_id, &1),
+            s.verification.get_milestone(&s.campaign_id, &1)
+        );
+    }
+
+    #[test]
+    fn test_checkins() {
+        let s = setup(1000);
+        s.verification.create_milestone(
+            &s.creator,
+            &s.campaign_id,
+            &String::from_str(&s.env, "Drill a well"),
+            &200,
+            &None,
+        );
+        let note = String::from_str(&s.env, "site visit: drilling on schedule");
+
+        let other = Address::generate(&s.env);
+        assert_eq!(
+            s.verification.try_checkin(&other, &s.campaign_id, &note),
+            Err(Ok(VerificationError::Unauthorized.into()))
+        );
+        let long_note = String::from_bytes(&s.env, &[b'a'; MAX_CHECKIN_NOTE_LEN as usize + 1]);
+        assert_eq!(
+            s.verification
+                .try_checkin(&s.verifier, &s.campaign_id, &long_note),
+            Err(Ok(VerificationError::NoteTooLong.into()))
+        );
+
+        s.env.ledger().with_mut(|li| li.timestamp = 500);
+        let checkin = s.verification.checkin(&s.verifier, &s.campaign_id, &note);
+        assert_eq!(checkin.timestamp, 500);
+        assert_eq!(
+            s.verification.get_checkins(&s.campaign_id),
+            vec![&s.env, checkin]
+        );
+        assert_eq!(
+            s.verification.get_milestone(&s.campaign_id, &0).status,
+            MilestoneStatus::Pending
+        );
+    }
+
+    #[test]
+    fn test_request_more_docs() {
+        let s = setup(1000);
+        s.verification.create_milestone(
+            &s.creator,
+            &s.campaign_id,
+            &String::from_str(&s.env, "Drill a well"),
+            &200,
+            &None,
+        );
+        let message = String::from_str(&s.env, "please attach the drilling log");
+
+        assert_eq!(
+            s.verification.try_request_more_docs(
+                &Address::generate(&s.env),
+                &s.campaign_id,
+                &0,
+                &message
+            ),
+            Err(Ok(VerificationError::Unauthorized.into()))
+        );
+        let requested = s
+            .verification
+            .request_more_docs(&s.verifier, &s.campaign_id, &0, &message);
+        assert_eq!(requested.status, MilestoneStatus::Pending);
+        assert!(requested.needs_more_info);
+        assert_eq!(requested.info_request, Some(message));
+
+        let docs = vec![&s.env, String::from_str(&s.env, "drilling-log.pdf")];
+        let provided = s
+            .verification
+            .provide_docs(&s.creator, &s.campaign_id, &0, &docs);
+        assert!(!provided.needs_more_info);
+        assert_eq!(provided.info_request, None);
+        assert_eq!(provided.verification_docs, docs);
+        assert_eq!(s.verification.get_milestone(&s.campaign_id, &0), provided);
+
+        s.verification
+            .verify_milestone(&s.verifier, &s.campaign_id, &0, &docs, &vec![&s.env]);
+        assert_eq!(
+            s.verification.try_request_more_docs(
+                &s.verifier,
+                &s.campaign_id,
+                &0,
+                &String::from_str(&s.env, "one more thing")
+            ),
+            Err(Ok(VerificationError::MilestoneNotPending.into()))
+        );
+    }
+
+    #[test]
+    fn test_clone_config() {
+        let s = setup(1000);
+        s.verification
+            .set_min_milestones(&s.creator, &s.campaign_id, &2);
+        s.verification.create_milestone(
+            &s.creator,
+            &s.campaign_id,
+            &String::from_str(&s.env, "Drill a well"),
+            &200,
+            &None,
+        );
+        let new_id = BytesN::from_array(&s.env, &[2; 32]);
+        s.campaign
+            .clone_campaign(&s.creator, &s.campaign_id, &new_id);
+
+        let config = s
+            .verification
+            .clone_config(&s.creator, &s.campaign_id, &new_id);
+        assert_eq!(config.min_milestones, 2);
+        assert_eq!(s.verification.get_config(&new_id), config);
+        assert!(s.verification.get_milestones(&new_id).is_empty());
+    }
+
+    #[test]
+    fn test_migrate_legacy_config() {
+        let s = setup(1000);
+        let legacy_id = BytesN::from_array(&s.env, &[2; 32]);
+        s.campaign
+            .clone_campaign(&s.creator, &s.campaign_id, &legacy_id);
+        let legacy = VerificationConfigV1 {
+            campaign_contract: s.campaign.address.clone(),
+            owner: s.creator.clone(),
+            verifier: s.verifier.clone(),
+        };
+        s.env.as_contract(&s.verification.address, || {
+            s.env
+                .storage()
+                .persistent()
+                .set(&(symbol_short!("cfg"), legacy_id.clone()), &legacy);
+        });
+
+        let config = s.verification.get_config(&legacy_id);
+        assert_eq!(config.verifier, s.verifier);
+        assert_eq!(config.max_milestones, DEFAULT_MAX_MILESTONES);
+        assert_eq!(config.threshold, 0);
+        assert_eq!(
+            s.verification
+                .try_migrate_config(&Address::generate(&s.env), &legacy_id),
+            Err(Ok(VerificationError::Unauthorized.into()))
+        );
+
+        assert_eq!(
+            s.verification.migrate_config(&s.creator, &legacy_id),
+            config
+        );
+        assert_eq!(
+            s.verification.migrate_config(&s.creator, &legacy_id),
+            config
+        );
+        s.env.as_contract(&s.verification.address, || {
+            let storage = s.env.storage().persistent();
+            assert!(!storage.has(&(symbol_short!("cfg"), legacy_id.clone())));
+            assert!(storage.has(&(symbol_short!("cfg_v2"), legacy_id.clone())));
+        });
+        s.verification.create_milestone(
+            &s.creator,
+            &legacy_id,
+            &String::from_str(&s.env, "Drill a well"),
+            &200,
+            &None,
+        );
+    }
+
+    #[test]
+    fn test_approval_ttl() {
+        let s = setup(1000);
+        s.campaign.add_donation(&s.campaign_id, &s.token, &500);
+        s.verification.create_milestone(
+            &s.creator,
+            &s.campaign_id,
+            &String::from_str(&s.env, "Drill a well"),
+            &200,
+            &None,
+        );
+        s.verification
+            .set_approval_ttl(&s.creator, &s.campaign_id, &Some(100));
+        let docs = vec![&s.env, String::from_str(&s.env, "report.pdf")];
+        let hashes = vec![&s.env, BytesN::from_array(&s.env, &[4; 32])];
+        s.env.ledger().with_mut(|li| li.timestamp = 1_000);
+        s.verification
+            .verify_milestone(&s.verifier, &s.campaign_id, &0, &docs, &hashes);
+
+        s.env.ledger().with_mut(|li| li.timestamp = 1_100);
+        assert_eq!(
+            s.verification
+                .try_revert_stale_verification(&s.campaign_id, &0),
+            Err(Ok(VerificationError::ApprovalNotStale.into()))
+        );
+
+        s.env.ledger().with_mut(|li| li.timestamp = 1_101);
+        assert_eq!(
+            s.verification
+                .try_complete_milestone(&s.verifier, &s.campaign_id, &0),
+            Err(Ok(VerificationError::MilestoneNotVerified.into()))
+        );
+        let reverted = s.verification.revert_stale_verification(&s.campaign_id, &0);
+        assert_eq!(reverted.status, MilestoneStatus::Pending);
+        assert_eq!(reverted.verified_at, None);
+        assert!(reverted.verification_docs.is_empty());
+        assert!(reverted.doc_hashes.is_empty());
+
+        s.verification
+            .verify_milestone(&s.verifier, &s.campaign_id, &0, &docs, &vec![&s.env]);
+        s.verification
+            .set_approval_ttl(&s.creator, &s.campaign_id, &Some(u64::MAX));
+        s.env.ledger().with_mut(|li| li.timestamp = 5_000);
+        let completed = s
+            .verification
+            .complete_milestone(&s.verifier, &s.campaign_id, &0);
+        assert_eq!(completed.status, MilestoneStatus::Completed);
+    }
+
+    #[test]
+    fn test_reject_milestone() {
+        let s = setup(1000);
+        s.campaign.add_donation(&s.campaign_id, &s.token, &500);
+        for _ in 0..2 {
+            s.verification.create_milestone(
+                &s.creator,
+                &s.campaign_id,
+                &String::from_str(&s.env, "Drill a well"),
+                &200,
+                &None,
+            );
+        }
+        let reason = String::from_str(&s.env, "well is dry");
+
+        let other = Address::generate(&s.env);
+        assert_eq!(
+            s.verification
+                .try_reject_milestone(&other, &s.campaign_id, &0, &reason),
+            Err(Ok(VerificationError::Unauthorized.into()))
+        );
+
+        let rejected = s
+            .verification
+            .reject_milestone(&s.verifier, &s.campaign_id, &0, &reason);
+        assert_eq!(rejected.status, MilestoneStatus::Failed);
+        assert_eq!(
+            s.verification
+                .try_reject_milestone(&s.verifier, &s.campaign_id, &0, &reason),
+            Err(Ok(VerificationError::MilestoneNotPending.into()))
+        );
+        assert_eq!(
+            s.verification
+                .try_complete_milestone(&s.verifier, &s.campaign_id, &0),
+            Err(Ok(VerificationError::MilestoneNotVerified.into()))
+        );
+
+        let docs = vec![&s.env, String::from_str(&s.env, "report.pdf")];
+        s.verification
+            .verify_milestone(&s.verifier, &s.campaign_id, &1, &docs, &vec![&s.env]);
+        let rejected = s
+            .verification
+            .reject_milestone(&s.verifier, &s.campaign_id, &1, &reason);
+        assert_eq!(rejected.status, MilestoneStatus::Failed);
+        assert_eq!(s.campaign.get(&s.campaign_id).released_amount, 0);
+    }
+
+    #[test]
+    fn test_complete_milestone_preconditions() {
+        let s = setup(1000);
+
+        let unconfigured = BytesN::from_array(&s.env, &[9; 32]);
+        assert_eq!(
+            s.verification
+                .try_complete_milestone(&s.verifier, &unconfigured, &0),
+            Err(Ok(VerificationError::NotConfigured.into()))
+        );
+        assert_eq!(
+            s.verification
+                .try_complete_milestone(&s.verifier, &s.campaign_id, &0),
+            Err(Ok(VerificationError::NoMilestones.into()))
+        );
+
+        s.verification.create_milestone(
+            &s.creator,
+            &s.campaign_id,
+            &String::from_str(&s.env, "Drill a well"),
+            &400,
+            &None,
+        );
+        assert_eq!(
+            s.verification
+                .try_complete_milestone(&s.verifier, &s.campaign_id, &1),
+            Err(Ok(VerificationError::MilestoneNotFound.into()))
         );
 
-        assert_eq!(milestone.status, MilestoneStatus::Pending);
+        let docs = vec![&s.env, String::from_str(&s.env, "report.pdf")];
+        s.verification
+            .verify_milestone(&s.verifier, &s.campaign_id, &0, &docs, &vec![&s.env]);
+        assert_eq!(
+            s.verification
+                .try_complete_milestone(&s.verifier, &s.campaign_id, &0),
+            Err(Ok(VerificationError::InsufficientFunds.into()))
+        );
+    }
 
-        let docs = vec![&env, String::from_str(&env, "report.pdf")];
-        let verified = verification_client.verify_milestone(&verifier, &campaign_id, &0, &docs);
+    #[test]
+    fn test_verify_and_complete() {
+        let s = setup(1000);
+        s.campaign.add_donation(&s.campaign_id, &s.token, &500);
+        s.verification.create_milestone(
+            &s.creator,
+            &s.campaign_id,
+            &String::from_str(&s.env, "Drill a well"),
+            &400,
+            &None,
+        );
+
+        let docs = vec![&s.env, String::from_str(&s.env, "report.pdf")];
+        let completed = s.verification.verify_and_complete(
+            &s.verifier,
+            &s.campaign_id,
+            &0,
+            &docs,
+            &vec![&s.env],
+        );
+        assert_eq!(completed.status, MilestoneStatus::Completed);
+        assert_eq!(completed.verified_by, Some(s.verifier.clone()));
+        assert_eq!(completed.verification_docs, docs);
+        assert_eq!(s.campaign.available_funds(&s.campaign_id), 100);
+
+        assert_eq!(
+            s.verification.try_verify_and_complete(
+                &s.verifier,
+                &s.campaign_id,
+                &0,
+                &docs,
+                &vec![&s.env]
+            ),
+            Err(Ok(VerificationError::MilestoneNotPending.into()))
+        );
+    }
+
+    #[test]
+    fn test_doc_budget() {
+        let s = setup(1000);
+        let admin = Address::generate(&s.env);
+        s.init_admin(&admin);
+        assert_eq!(
+            s.verification
+                .try_set_doc_budget(&s.creator, &s.campaign_id, &20),
+            Err(Ok(VerificationError::Unauthorized.into()))
+        );
+        let config = s.verification.set_doc_budget(&admin, &s.campaign_id, &20);
+        assert_eq!(config.max_doc_bytes, 20);
+
+        s.verification.create_milestone(
+            &s.creator,
+            &s.campaign_id,
+            &String::from_str(&s.env, "Drill a well"),
+            &400,
+            &None,
+        );
+        let too_large = vec![
+            &s.env,
+            String::from_str(&s.env, "report.pdf"),
+            String::from_str(&s.env, "photos.zip!"),
+        ];
+        assert_eq!(
+            s.verification.try_verify_milestone(
+                &s.verifier,
+                &s.campaign_id,
+                &0,
+                &too_large,
+                &vec![&s.env]
+            ),
+            Err(Ok(VerificationError::DocsTooLarge.into()))
+        );
+
+        let within_budget = vec![
+            &s.env,
+            String::from_str(&s.env, "report.pdf"),
+            String::from_str(&s.env, "photos.zip"),
+        ];
+        // Each hash counts as 32 bytes against the same budget.
+        assert_eq!(
+            s.verification.try_verify_milestone(
+                &s.verifier,
+                &s.campaign_id,
+                &0,
+                &within_budget,
+                &vec![&s.env, BytesN::from_array(&s.env, &[1; 32])]
+            ),
+            Err(Ok(VerificationError::DocsTooLarge.into()))
+        );
+        let verified = s.verification.verify_milestone(
+            &s.verifier,
+            &s.campaign_id,
+            &0,
+            &within_budget,
+            &vec![&s.env],
+        );
         assert_eq!(verified.status, MilestoneStatus::Verified);
+    }
 
-        let completed = verification_client.complete_milestone(&verifier, &campaign_id, &0);
+    #[test]
+    fn test_min_docs() {
+        let s = setup(1000);
+        s.verification.create_milestone(
+            &s.creator,
+            &s.campaign_id,
+            &String::from_str(&s.env, "Drill a well"),
+            &400,
+            &None,
+        );
+        assert_eq!(
+            s.verification.try_verify_milestone(
+                &s.verifier,
+                &s.campaign_id,
+                &0,
+                &vec![&s.env],
+                &vec![&s.env]
+            ),
+            Err(Ok(VerificationError::InsufficientDocs.into()))
+        );
+
+        assert_eq!(
+            s.verification
+                .try_set_min_docs(&s.verifier, &s.campaign_id, &3),
+            Err(Ok(VerificationError::Unauthorized.into()))
+        );
+        s.verification.set_min_docs(&s.creator, &s.campaign_id, &3);
+
+        let mut docs = vec![
+            &s.env,
+            String::from_str(&s.env, "report.pdf"),
+            String::from_str(&s.env, "photos.zip"),
+        ];
+        assert_eq!(
+            s.verification.try_verify_milestone(
+                &s.verifier,
+                &s.campaign_id,
+                &0,
+                &docs,
+                &vec![&s.env]
+            ),
+            Err(Ok(VerificationError::InsufficientDocs.into()))
+        );
+        docs.push_back(String::from_str(&s.env, "invoice.pdf"));
+        let verified =
+            s.verification
+                .verify_milestone(&s.verifier, &s.campaign_id, &0, &docs, &vec![&s.env]);
+        assert_eq!(verified.verification_docs.len(), 3);
+    }
+
+    #[test]
+    fn test_verify_doc() {
+        let s = setup(1000);
+        s.verification.create_milestone(
+            &s.creator,
+            &s.campaign_id,
+            &String::from_str(&s.env, "Drill a well"),
+            &400,
+            &None,
+        );
+
+        let report = Bytes::from_slice(&s.env, b"well drilled to 40m, water tested");
+        let docs = vec![&s.env, String::from_str(&s.env, "report.pdf")];
+        let hashes = vec![&s.env, s.env.crypto().sha256(&report).into()];
+        let verified =
+            s.verification
+                .verify_milestone(&s.verifier, &s.campaign_id, &0, &docs, &hashes);
+        assert_eq!(verified.doc_hashes, hashes);
+
+        assert!(s.verification.verify_doc(&s.campaign_id, &0, &report));
+        let tampered = Bytes::from_slice(&s.env, b"well drilled to 4m, water tested");
+        assert!(!s.verification.verify_doc(&s.campaign_id, &0, &tampered));
+    }
+
+    #[test]
+    fn test_in_kind_milestone() {
+        let s = setup(1000);
+        s.verification.create_milestone(
+            &s.creator,
+            &s.campaign_id,
+            &String::from_str(&s.env, "Submit progress report"),
+            &0,
+            &None,
+        );
+        assert_eq!(
+            s.verification.try_create_milestone(
+                &s.creator,
+                &s.campaign_id,
+                &String::from_str(&s.env, "Negative"),
+                &-1,
+                &None
+            ),
+            Err(Ok(VerificationError::InvalidAmount.into()))
+        );
+
+        let docs = vec![&s.env, String::from_str(&s.env, "report.pdf")];
+        s.verification
+            .verify_milestone(&s.verifier, &s.campaign_id, &0, &docs, &vec![&s.env]);
+        assert_eq!(
+            s.verification.simulate_complete(&s.campaign_id, &0),
+            (0, CampaignStatus::Active)
+        );
+
+        let completed = s
+            .verification
+            .complete_milestone(&s.verifier, &s.campaign_id, &0);
         assert_eq!(completed.status, MilestoneStatus::Completed);
         assert!(completed.completed_at.is_some());
+        assert_eq!(s.campaign.get(&s.campaign_id).released_amount, 0);
+    }
+
+    #[test]
+    fn test_milestone_durations() {
+        let s = setup(1000);
+        s.env.ledger().with_mut(|li| li.timestamp = 1_000);
+        s.verification.create_milestone(
+            &s.creator,
+            &s.campaign_id,
+            &String::from_str(&s.env, "Build a school"),
+            &0,
+            &None,
+        );
+
+        s.env.ledger().with_mut(|li| li.timestamp = 1_300);
+        assert_eq!(
+            s.verification.milestone_durations(&s.campaign_id, &0),
+            (300, 0)
+        );
+
+        s.env.ledger().with_mut(|li| li.timestamp = 1_500);
+        let docs = vec![&s.env, String::from_str(&s.env, "photos.zip")];
+        s.verification
+            .verify_milestone(&s.verifier, &s.campaign_id, &0, &docs, &vec![&s.env]);
+
+        s.env.ledger().with_mut(|li| li.timestamp = 1_600);
+        assert_eq!(
+            s.verification.milestone_durations(&s.campaign_id, &0),
+            (500, 100)
+        );
+
+        s.env.ledger().with_mut(|li| li.timestamp = 1_750);
+        s.verification
+            .complete_milestone(&s.verifier, &s.campaign_id, &0);
+
+        s.env.ledger().with_mut(|li| li.timestamp = 9_999);
+        assert_eq!(
+            s.verification.milestone_durations(&s.campaign_id, &0),
+            (500, 250)
+        );
+    }
+
+    #[test]
+    fn test_cleanup() {
+        let s = setup(1000);
+        s.verification.create_milestone(
+            &s.creator,
+            &s.campaign_id,
+            &String::from_str(&s.env, "Build a school"),
+            &400,
+            &None,
+        );
+        assert_eq!(
+            s.verification.try_cleanup(&s.creator, &s.campaign_id),
+            Err(Ok(VerificationError::CampaignNotFinished.into()))
+        );
+
+        s.campaign.cancel(&s.creator, &s.campaign_id);
+        assert_eq!(
+            s.verification
+                .try_cleanup(&Address::generate(&s.env), &s.campaign_id),
+            Err(Ok(VerificationError::Unauthorized.into()))
+        );
+        s.verification.cleanup(&s.creator, &s.campaign_id);
+
+        assert_eq!(
+            s.verification.try_get_milestones(&s.campaign_id),
+            Err(Ok(VerificationError::Purged.into()))
+        );
+        assert_eq!(
+            s.verification.try_get_milestone(&s.campaign_id, &0),
+            Err(Ok(VerificationError::Purged.into()))
+        );
+        assert_eq!(
+            s.verification.try_get_config(&s.campaign_id),
+            Err(Ok(VerificationError::Purged.into()))
+        );
+        assert_eq!(
+            s.verification.get_tombstone(&s.campaign_id),
+            Some(Tombstone {
+                purged_at: s.env.ledger().timestamp(),
+                reason: CampaignStatus::Cancelled,
+            })
+        );
+        assert_eq!(
+            s.verification
+                .try_get_config(&BytesN::from_array(&s.env, &[9; 32])),
+            Err(Ok(VerificationError::NotConfigured.into()))
+        );
+
+        // Milestone ids keep counting after a purge instead of restarting at 0.
+        s.verification.configure_campaign(
+            &s.creator,
+            &s.campaign.address,
+            &s.campaign_id,
+            &s.verifier,
+            &None,
+        );
+        let milestone = s.verification.create_milestone(
+            &s.creator,
+            &s.campaign_id,
+            &String::from_str(&s.env, "Rebuild"),
+            &0,
+            &None,
+        );
+        assert_eq!(milestone.id, 1);
+    }
+
+    #[test]
+    fn test_get_milestones_by_status() {
+        let s = setup(1000);
+        for description in ["Plan", "Build", "Report"] {
+            s.verification.create_milestone(
+                &s.creator,
+                &s.campaign_id,
+                &String::from_str(&s.env, description),
+                &0,
+                &None,
+            );
+        }
+        let docs = vec![&s.env, String::from_str(&s.env, "proof.pdf")];
+        let verified =
+            s.verification
+                .verify_milestone(&s.verifier, &s.campaign_id, &1, &docs, &vec![&s.env]);
+
+        assert_eq!(
+            s.verification
+                .get_milestones_by_status(&s.campaign_id, &MilestoneStatus::Verified),
+            vec![&s.env, (1, verified)]
+        );
+        assert_eq!(
+            s.verification
+                .get_milestones_by_status(&s.campaign_id, &MilestoneStatus::Pending)
+                .len(),
+            2
+        );
+        assert_eq!(
+            s.verification
+                .get_milestones_by_status(&s.campaign_id, &MilestoneStatus::Completed)
+                .len(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_describe_error() {
+        let s = setup(1000);
+        assert_eq!(
+            s.verification
+                .describe_error(&(VerificationError::Unauthorized as u32)),
+            symbol_short!("unauth")
+        );
+        for code in 1..=26 {
+            assert_ne!(
+                s.verification.describe_error(&code),
+                symbol_short!("unknown")
+            );
+        }
+        assert_eq!(s.verification.describe_error(&27), symbol_short!("unknown"));
+        assert_eq!(s.verification.describe_error(&0), symbol_short!("unknown"));
+    }
+
+    #[test]
+    fn test_verify_milestones_batch() {
+        let s = setup(1000);
+        for description in ["Plan", "Build", "Report"] {
+            s.verification.create_milestone(
+                &s.creator,
+                &s.campaign_id,
+                &String::from_str(&s.env, description),
+                &0,
+                &None,
+            );
+        }
+        let docs = vec![&s.env, String::from_str(&s.env, "proof.pdf")];
+
+        assert_eq!(
+            s.verification.try_verify_milestones(
+                &s.verifier,
+                &s.campaign_id,
+                &vec![&s.env, 0, 1],
+                &vec![&s.env, docs.clone(), vec![&s.env]],
+            ),
+            Err(Ok(VerificationError::InsufficientDocs.into()))
+        );
+        assert_eq!(
+            s.verification.try_verify_milestones(
+                &s.verifier,
+                &s.campaign_id,
+                &vec![&s.env, 0, 0],
+                &vec![&s.env, docs.clone(), docs.clone()],
+            ),
+            Err(Ok(VerificationError::MilestoneNotPending.into()))
+        );
+        assert_eq!(
+            s.verification
+                .get_milestones_by_status(&s.campaign_id, &MilestoneStatus::Pending)
+                .len(),
+            3
+        );
+
+        let verified = s.verification.verify_milestones(
+            &s.verifier,
+            &s.campaign_id,
+            &vec![&s.env, 0, 2],
+            &vec![&s.env, docs.clone(), docs],
+        );
+        assert_eq!(verified.len(), 2);
+        assert_eq!(
+            s.verification.get_milestone(&s.campaign_id, &2).status,
+            MilestoneStatus::Verified
+        );
+        assert_eq!(
+            s.verification.get_milestone(&s.campaign_id, &1).status,
+            MilestoneStatus::Pending
+        );
+    }
+
+    #[test]
+    fn test_is_milestone_funded() {
+        let s = setup(1000);
+        s.verification.create_milestone(
+            &s.creator,
+            &s.campaign_id,
+            &String::from_str(&s.env, "Build a school"),
+            &400,
+            &None,
+        );
+        assert!(!s.verification.is_milestone_funded(&s.campaign_id, &0));
+
+        s.campaign.add_donation(&s.campaign_id, &s.token, &300);
+        assert!(!s.verification.is_milestone_funded(&s.campaign_id, &0));
+        s.campaign.add_donation(&s.campaign_id, &s.token, &100);
+        assert!(s.verification.is_milestone_funded(&s.campaign_id, &0));
+    }
+
+    #[test]
+    fn test_release_auth_entry_matches_campaign_call() {
+        let s = setup(1000);
+        let InvokerContractAuthEntry::Contract(invocation) =
+            VerificationContract::release_auth_entry(
+                &s.env,
+                &s.campaign.address,
+                &s.campaign_id,
+                400,
+            )
+        else {
+            panic!("expected a contract auth entry");
+        };
+        assert_eq!(invocation.context.contract, s.campaign.address);
+        assert_eq!(
+            invocation.context.fn_name,
+            Symbol::new(&s.env, "mark_milestone_completed")
+        );
+        let expected: Vec<Val> = (s.campaign_id.clone(), 400i128).into_val(&s.env);
+        assert_eq!(invocation.context.args, expected);
+        assert!(invocation.sub_invocations.is_empty());
+    }
+
+    #[test]
+    fn test_complete_milestone_needs_only_verifier_auth() {
+        let s = setup(1000);
+        s.verification.create_milestone(
+            &s.creator,
+            &s.campaign_id,
+            &String::from_str(&s.env, "Build a school"),
+            &400,
+            &None,
+        );
+        s.campaign.add_donation(&s.campaign_id, &s.token, &500);
+        let docs = vec![&s.env, String::from_str(&s.env, "proof.pdf")];
+        s.verification
+            .verify_milestone(&s.verifier, &s.campaign_id, &0, &docs, &vec![&s.env]);
+
+        let invoke = MockAuthInvoke {
+            contract: &s.verification.address,
+            fn_name: "complete_milestone",
+            args: (s.verifier.clone(), s.campaign_id.clone(), 0u32).into_val(&s.env),
+            sub_invokes: &[],
+        };
+        s.verification
+            .mock_auths(&[MockAuth {
+                address: &s.verifier,
+                invoke: &invoke,
+            }])
+            .complete_milestone(&s.verifier, &s.campaign_id, &0);
+        assert_eq!(s.campaign.get(&s.campaign_id).released_amount, 400);
+    }
+
+    #[test]
+    fn test_failure_threshold_cancels_campaign() {
+        let s = setup(1000);
+        for _ in 0..3 {
+            s.verification.create_milestone(
+                &s.creator,
+                &s.campaign_id,
+                &String::from_str(&s.env, "Drill a well"),
+                &100,
+                &None,
+            );
+        }
+        s.verification
+            .set_failure_threshold(&s.creator, &s.campaign_id, &1);
+        let reason = String::from_str(&s.env, "no progress");
+
+        s.verification
+            .reject_milestone(&s.verifier, &s.campaign_id, &0, &reason);
+        assert_eq!(s.verification.failed_milestones(&s.campaign_id), 1);
+        assert_eq!(s.campaign.status(&s.campaign_id), CampaignStatus::Active);
+
+        s.verification
+            .reject_milestone(&s.verifier, &s.campaign_id, &1, &reason);
+        assert_eq!(s.verification.failed_milestones(&s.campaign_id), 2);
+        assert_eq!(s.campaign.status(&s.campaign_id), CampaignStatus::Cancelled);
+
+        s.verification
+            .reject_milestone(&s.verifier, &s.campaign_id, &2, &reason);
+        assert_eq!(s.verification.failed_milestones(&s.campaign_id), 3);
+        assert_eq!(s.campaign.status(&s.campaign_id), CampaignStatus::Cancelled);
+    }
+
+    #[test]
+    fn test_failure_threshold_skips_completed_campaign() {
+        let s = setup(1000);
+        s.campaign.add_donation(&s.campaign_id, &s.token, &1000);
+        for amount in [1000, 0, 0] {
+            s.verification.create_milestone(
+                &s.creator,
+                &s.campaign_id,
+                &String::from_str(&s.env, "Drill a well"),
+                &amount,
+                &None,
+            );
+        }
+        let docs = vec![&s.env, String::from_str(&s.env, "report.pdf")];
+        s.verification
+            .verify_milestone(&s.verifier, &s.campaign_id, &0, &docs, &vec![&s.env]);
+        s.verification
+            .complete_milestone(&s.verifier, &s.campaign_id, &0);
+        assert_eq!(s.campaign.status(&s.campaign_id), CampaignStatus::Completed);
+
+        s.verification
+            .set_failure_threshold(&s.creator, &s.campaign_id, &1);
+        let reason = String::from_str(&s.env, "no report");
+        s.verification
+            .reject_milestone(&s.verifier, &s.campaign_id, &1, &reason);
+        s.verification
+            .reject_milestone(&s.verifier, &s.campaign_id, &2, &reason);
+        assert_eq!(s.verification.failed_milestones(&s.campaign_id), 2);
+        assert_eq!(s.campaign.status(&s.campaign_id), CampaignStatus::Completed);
+    }
+
+    #[test]
+    fn test_campaign_call_failed() {
+        let s = setup(1000);
+        s.campaign.add_donation(&s.campaign_id, &s.token, &500);
+        s.verification.create_milestone(
+            &s.creator,
+            &s.campaign_id,
+            &String::from_str(&s.env, "Drill a well"),
+            &200,
+            &None,
+        );
+        let docs = vec![&s.env, String::from_str(&s.env, "report.pdf")];
+        s.verification
+            .verify_milestone(&s.verifier, &s.campaign_id, &0, &docs, &vec![&s.env]);
+
+        // The campaign now trusts a different verification contract, so it
+        // rejects the release.
+        let donation_contract = s.campaign.get(&s.campaign_id).donation_contract;
+        s.campaign.set_authorized_contracts(
+            &s.creator,
+            &s.campaign_id,
+            &donation_contract,
+            &Some(Address::generate(&s.env)),
+        );
+        assert_eq!(
+            s.verification
+                .try_complete_milestone(&s.verifier, &s.campaign_id, &0),
+            Err(Ok(VerificationError::CampaignCallFailed.into()))
+        );
+        s.campaign.set_authorized_contracts(
+            &s.creator,
+            &s.campaign_id,
+            &donation_contract,
+            &Some(s.verification.address.clone()),
+        );
+        s.verification
+            .complete_milestone(&s.verifier, &s.campaign_id, &0);
+
+        let not_a_campaign = s.verification.address.clone();
+        assert_eq!(
+            s.verification.try_configure_campaign(
+                &s.creator,
+                &not_a_campaign,
+                &s.campaign_id,
+                &s.verifier,
+                &None
+            ),
+            Err(Ok(VerificationError::CampaignCallFailed.into()))
+        );
+    }
+
+    #[test]
+    fn test_verifier_blacklist() {
+        let s = setup(1000);
+        s.campaign.add_donation(&s.campaign_id, &s.token, &500);
+        let description = String::from_str(&s.env, "Drill a well");
+        for _ in 0..2 {
+            s.verification
+                .create_milestone(&s.creator, &s.campaign_id, &description, &200, &None);
+        }
+        let docs = vec![&s.env, String::from_str(&s.env, "report.pdf")];
+        s.verification
+            .verify_milestone(&s.verifier, &s.campaign_id, &0, &docs, &vec![&s.env]);
+
+        let admin = Address::generate(&s.env);
+        s.init_admin(&admin);
+        s.verification
+            .set_verifier_blacklisted(&admin, &s.verifier, &true);
+        assert!(s.verification.is_verifier_blacklisted(&s.verifier));
+
+        assert_eq!(
+            s.verification
+                .try_complete_milestone(&s.verifier, &s.campaign_id, &0),
+            Err(Ok(VerificationError::VerifierBlacklisted.into()))
+        );
+        assert_eq!(
+            s.verification.try_verify_milestone(
+                &s.verifier,
+                &s.campaign_id,
+                &1,
+                &docs,
+                &vec![&s.env]
+            ),
+            Err(Ok(VerificationError::VerifierBlacklisted.into()))
+        );
+        assert_eq!(
+            s.verification.try_configure_campaign(
+                &s.creator,
+                &s.campaign.address,
+                &s.campaign_id,
+                &s.verifier,
+                &None
+            ),
+            Err(Ok(VerificationError::VerifierBlacklisted.into()))
+        );
+        let note = String::from_str(&s.env, "site visit");
+        assert_eq!(
+            s.verification
+                .try_revoke_verification(&s.verifier, &s.campaign_id, &0),
+            Err(Ok(VerificationError::VerifierBlacklisted.into()))
+        );
+        assert_eq!(
+            s.verification
+                .try_request_more_docs(&s.verifier, &s.campaign_id, &1, &note),
+            Err(Ok(VerificationError::VerifierBlacklisted.into()))
+        );
+        assert_eq!(
+            s.verification
+                .try_reject_milestone(&s.verifier, &s.campaign_id, &1, &note),
+            Err(Ok(VerificationError::VerifierBlacklisted.into()))
+        );
+        assert_eq!(
+            s.verification
+                .try_checkin(&s.verifier, &s.campaign_id, &note),
+            Err(Ok(VerificationError::VerifierBlacklisted.into()))
+        );
+        assert_eq!(s.verification.next_payable_milestone(&s.campaign_id), None);
+
+        s.verification
+            .set_verifier_blacklisted(&admin, &s.verifier, &false);
+        let completed = s
+            .verification
+            .complete_milestone(&s.verifier, &s.campaign_id, &0);
+        assert_eq!(completed.status, MilestoneStatus::Completed);
+
+        // A milestone approved by a verifier blacklisted since is not payable,
+        // even once another verifier takes over.
+        s.verification
+            .verify_milestone(&s.verifier, &s.campaign_id, &1, &docs, &vec![&s.env]);
+        let successor = Address::generate(&s.env);
+        s.verification.configure_campaign(
+            &s.creator,
+            &s.campaign.address,
+            &s.campaign_id,
+            &successor,
+            &None,
+        );
+        s.verification
+            .set_verifier_blacklisted(&admin, &s.verifier, &true);
+        assert_eq!(s.verification.next_payable_milestone(&s.campaign_id), None);
+        assert_eq!(
+            s.verification
+                .try_complete_milestone(&successor, &s.campaign_id, &1),
+            Err(Ok(VerificationError::VerifierBlacklisted.into()))
+        );
+    }
+
+    #[test]
+    fn test_require_funded_to_complete() {
+        let s = setup(1000);
+        s.campaign.add_donation(&s.campaign_id, &s.token, &600);
+        s.verification.create_milestone(
+            &s.creator,
+            &s.campaign_id,
+            &String::from_str(&s.env, "Drill a well"),
+            &400,
+            &None,
+        );
+        let docs = vec![&s.env, String::from_str(&s.env, "report.pdf")];
+        s.verification
+            .verify_milestone(&s.verifier, &s.campaign_id, &0, &docs, &vec![&s.env]);
+
+        let config =
+            s.verification
+                .set_require_funded_to_complete(&s.creator, &s.campaign_id, &true);
+        assert!(config.require_funded_to_complete);
+        assert_eq!(
+            s.verification
+                .try_complete_milestone(&s.verifier, &s.campaign_id, &0),
+            Err(Ok(VerificationError::CampaignNotFunded.into()))
+        );
+        assert_eq!(s.verification.next_payable_milestone(&s.campaign_id), None);
+
+        s.campaign.add_donation(&s.campaign_id, &s.token, &400);
+        assert_eq!(
+            s.verification.next_payable_milestone(&s.campaign_id),
+            Some(0)
+        );
+        let completed = s
+            .verification
+            .complete_milestone(&s.verifier, &s.campaign_id, &0);
+        assert_eq!(completed.status, MilestoneStatus::Completed);
+        assert_eq!(s.campaign.get(&s.campaign_id).released_amount, 400);
+    }
+
+    #[test]
+    fn test_min_milestones_gate_release() {
+        let s = setup(1000);
+        s.campaign.add_donation(&s.campaign_id, &s.token, &1000);
+        assert_eq!(
+            s.verification
+                .try_set_min_milestones(&s.creator, &s.campaign_id, &4),
+            Err(Ok(VerificationError::TooManyMilestones.into()))
+        );
+        s.verification
+            .set_min_milestones(&s.creator, &s.campaign_id, &2);
+
+        s.verification.create_milestone(
+            &s.creator,
+            &s.campaign_id,
+            &String::from_str(&s.env, "Build a school"),
+            &400,
+            &None,
+        );
+        let docs = vec![&s.env, String::from_str(&s.env, "proof.pdf")];
+        s.verification
+            .verify_milestone(&s.verifier, &s.campaign_id, &0, &docs, &vec![&s.env]);
+        assert_eq!(
+            s.verification
+                .try_complete_milestone(&s.verifier, &s.campaign_id, &0),
+            Err(Ok(VerificationError::TooFewMilestones.into()))
+        );
+
+        s.verification.create_milestone(
+            &s.creator,
+            &s.campaign_id,
+            &String::from_str(&s.env, "Report"),
+            &0,
+            &None,
+        );
+        let completed = s
+            .verification
+            .complete_milestone(&s.verifier, &s.campaign_id, &0);
+        assert_eq!(completed.status, MilestoneStatus::Completed);
+    }
+
+    #[test]
+    fn test_docs_expiry() {
+        let s = setup(1000);
+        s.verification
+            .set_docs_valid_secs(&s.creator, &s.campaign_id, &Some(100));
+        for description in ["Plan", "Build"] {
+            s.verification.create_milestone(
+                &s.creator,
+                &s.campaign_id,
+                &String::from_str(&s.env, description),
+                &0,
+                &None,
+            );
+        }
+        let docs = vec![&s.env, String::from_str(&s.env, "proof.pdf")];
+
+        s.env.ledger().with_mut(|li| li.timestamp = 1_000);
+        s.verification
+            .verify_milestone(&s.verifier, &s.campaign_id, &0, &docs, &vec![&s.env]);
+        s.verification
+            .verify_milestone(&s.verifier, &s.campaign_id, &1, &docs, &vec![&s.env]);
+
+        s.env.ledger().with_mut(|li| li.timestamp = 1_100);
+        s.verification
+            .complete_milestone(&s.verifier, &s.campaign_id, &0);
+
+        s.env.ledger().with_mut(|li| li.timestamp = 1_101);
+        assert_eq!(
+            s.verification
+                .try_complete_milestone(&s.verifier, &s.campaign_id, &1),
+            Err(Ok(VerificationError::DocsExpired.into()))
+        );
+        assert_eq!(s.verification.next_payable_milestone(&s.campaign_id), None);
+
+        s.verification
+            .revoke_verification(&s.verifier, &s.campaign_id, &1);
+        s.verification
+            .verify_milestone(&s.verifier, &s.campaign_id, &1, &docs, &vec![&s.env]);
+        s.verification
+            .set_docs_valid_secs(&s.creator, &s.campaign_id, &Some(u64::MAX));
+        assert_eq!(
+            s.verification.next_payable_milestone(&s.campaign_id),
+            Some(1)
+        );
+        s.verification
+            .complete_milestone(&s.verifier, &s.campaign_id, &1);
+    }
+
+    #[test]
+    fn test_verify_milestone_multisig() {
+        use ed25519_dalek::{Signer, SigningKey};
+
+        let s = setup(1000);
+        s.verification.create_milestone(
+            &s.creator,
+            &s.campaign_id,
+            &String::from_str(&s.env, "Build a school"),
+            &0,
+            &None,
+        );
+
+        let keys = [1u8, 2, 3].map(|seed| SigningKey::from_bytes(&[seed; 32]));
+        let signers = [0, 1, 2].map(|_| Address::generate(&s.env));
+        let mut verifier_keys = Map::new(&s.env);
+        for (signer, key) in signers.iter().zip(keys.iter()) {
+            verifier_keys.set(
+                signer.clone(),
+                BytesN::from_array(&s.env, &key.verifying_key().to_bytes()),
+            );
+        }
+        assert_eq!(
+            s.verification
+                .try_set_verifier_set(&s.creator, &s.campaign_id, &verifier_keys, &4),
+            Err(Ok(VerificationError::InvalidThreshold.into()))
+        );
+        s.verification
+            .set_verifier_set(&s.creator, &s.campaign_id, &verifier_keys, &2);
+
+        let docs = vec![&s.env, String::from_str(&s.env, "inspection.pdf")];
+        let sign = |index: usize| {
+            let digest = s
+                .verification
+                .multisig_digest(&s.campaign_id, &0, &docs)
+                .to_array();
+            (
+                signers[index].clone(),
+                BytesN::from_array(&s.env, &keys[index].sign(&digest).to_bytes()),
+            )
+        };
+
+        assert_eq!(
+            s.verification.try_verify_milestone_multisig(
+                &s.campaign_id,
+                &0,
+                &vec![&s.env, sign(0), sign(0)],
+                &docs,
+            ),
+            Err(Ok(VerificationError::DuplicateSigner.into()))
+        );
+        assert_eq!(
+            s.verification.try_verify_milestone_multisig(
+                &s.campaign_id,
+                &0,
+                &vec![&s.env, sign(2)],
+                &docs,
+            ),
+            Err(Ok(VerificationError::InsufficientSignatures.into()))
+        );
+
+        let verified = s.verification.verify_milestone_multisig(
+            &s.campaign_id,
+            &0,
+            &vec![&s.env, sign(1), sign(2)],
+            &docs,
+        );
+        assert_eq!(verified.status, MilestoneStatus::Verified);
+        assert_eq!(verified.verified_by, Some(signers[1].clone()));
+
+        // Revoking bumps the nonce, so the same signatures cannot be replayed.
+        let replayed = vec![&s.env, sign(1), sign(2)];
+        s.verification
+            .revoke_verification(&signers[1], &s.campaign_id, &0);
+        assert!(s
+            .verification
+            .try_verify_milestone_multisig(&s.campaign_id, &0, &replayed, &docs)
+            .is_err());
+
+        let admin = Address::generate(&s.env);
+        s.init_admin(&admin);
+        s.verification
+            .set_verifier_blacklisted(&admin, &signers[2], &true);
+        assert_eq!(
+            s.verification.try_verify_milestone_multisig(
+                &s.campaign_id,
+                &0,
+                &vec![&s.env, sign(0), sign(2)],
+                &docs,
+            ),
+            Err(Ok(VerificationError::VerifierBlacklisted.into()))
+        );
+        let verified = s.verification.verify_milestone_multisig(
+            &s.campaign_id,
+            &0,
+            &vec![&s.env, sign(0), sign(1)],
+            &docs,
+        );
+        assert_eq!(verified.nonce, 1);
+    }
+
+    #[test]
+    fn test_verify_milestone_multisig_rejects_bad_signature() {
+        use ed25519_dalek::{Signer, SigningKey};
+
+        let s = setup(1000);
+        s.verification.create_milestone(
+            &s.creator,
+            &s.campaign_id,
+            &String::from_str(&s.env, "Build a school"),
+            &0,
+            &None,
+        );
+        let key = SigningKey::from_bytes(&[1; 32]);
+        let signer = Address::generate(&s.env);
+        let mut verifier_keys = Map::new(&s.env);
+        verifier_keys.set(
+            signer.clone(),
+            BytesN::from_array(&s.env, &key.verifying_key().to_bytes()),
+        );
+        s.verification
+            .set_verifier_set(&s.creator, &s.campaign_id, &verifier_keys, &1);
+
+        let docs = vec![&s.env, String::from_str(&s.env, "inspection.pdf")];
+        let other_docs = vec![&s.env, String::from_str(&s.env, "forged.pdf")];
+        let digest = s
+            .verification
+            .multisig_digest(&s.campaign_id, &0, &other_docs)
+            .to_array();
+        let signature = BytesN::from_array(&s.env, &key.sign(&digest).to_bytes());
+        assert!(s
+            .verification
+            .try_verify_milestone_multisig(
+                &s.campaign_id,
+                &0,
+                &vec![&s.env, (signer, signature)],
+                &docs,
+            )
+            .is_err());
+        assert_eq!(
+            s.verification.get_milestone(&s.campaign_id, &0).status,
+            MilestoneStatus::Pending
+        );
+    }
+
+    #[test]
+    fn test_milestone_dependencies() {
+        let s = setup(1000);
+        s.verification.create_milestone(
+            &s.creator,
+            &s.campaign_id,
+            &String::from_str(&s.env, "Buy land"),
+            &0,
+            &None,
+        );
+        s.verification.create_milestone(
+            &s.creator,
+            &s.campaign_id,
+            &String::from_str(&s.env, "Hire builders"),
+            &0,
+            &None,
+        );
+        assert_eq!(
+            s.verification.try_create_milestone(
+                &s.creator,
+                &s.campaign_id,
+                &String::from_str(&s.env, "Build"),
+                &0,
+                &Some(2),
+            ),
+            Err(Ok(VerificationError::MilestoneNotFound.into()))
+        );
+        s.verification.create_milestone(
+            &s.creator,
+            &s.campaign_id,
+            &String::from_str(&s.env, "Build"),
+            &0,
+            &Some(0),
+        );
+
+        let docs = vec![&s.env, String::from_str(&s.env, "proof.pdf")];
+        for index in 0..3u32 {
+            s.verification.verify_milestone(
+                &s.verifier,
+                &s.campaign_id,
+                &index,
+                &docs,
+                &vec![&s.env],
+            );
+        }
+        assert_eq!(
+            s.verification
+                .try_complete_milestone(&s.verifier, &s.campaign_id, &2),
+            Err(Ok(VerificationError::DependencyNotMet.into()))
+        );
+        s.verification
+            .complete_milestone(&s.verifier, &s.campaign_id, &1);
+        assert_eq!(
+            s.verification
+                .try_complete_milestone(&s.verifier, &s.campaign_id, &2),
+            Err(Ok(VerificationError::DependencyNotMet.into()))
+        );
+        s.verification
+            .complete_milestone(&s.verifier, &s.campaign_id, &0);
+        s.verification
+            .complete_milestone(&s.verifier, &s.campaign_id, &2);
     }
 }
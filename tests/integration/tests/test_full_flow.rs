@@ -1,7 +1,7 @@
 use givehub_campaign::{CampaignContract, CampaignContractClient, CampaignStatus};
 use givehub_donation::{DonationContract, DonationContractClient};
 use givehub_verification::{MilestoneStatus, VerificationContract, VerificationContractClient};
-use soroban_sdk::{testutils::Address as _, vec, Address, BytesN, Env, String};
+use soroban_sdk::{symbol_short, testutils::Address as _, vec, Address, BytesN, Env, String};
 
 #[test]
 fn test_full_campaign_flow() {
@@ -10,6 +10,9 @@ fn test_full_campaign_flow() {
     let donor = Address::generate(&env);
     let creator = Address::generate(&env);
     let verifier = Address::generate(&env);
+    let token = env
+        .register_stellar_asset_contract_v2(Address::generate(&env))
+        .address();
 
     let campaign_addr = env.register_contract(None, CampaignContract);
     let donation_addr = env.register_contract(None, DonationContract);
@@ -27,6 +30,8 @@ fn test_full_campaign_flow() {
         &String::from_str(&env, "Test Campaign"),
         &String::from_str(&env, "Test Description"),
         &1000,
+        &Some(token.clone()),
+        &false,
     );
 
     campaign_client.set_authorized_contracts(
@@ -36,7 +41,13 @@ fn test_full_campaign_flow() {
         &Some(verification_addr.clone()),
     );
 
-    verification_client.configure_campaign(&creator, &campaign_addr, &campaign_id, &verifier);
+    verification_client.configure_campaign(
+        &creator,
+        &campaign_addr,
+        &campaign_id,
+        &verifier,
+        &None,
+    );
 
     env.mock_all_auths();
     let active_campaign = campaign_client.activate(&creator, &campaign_id);
@@ -47,10 +58,19 @@ fn test_full_campaign_flow() {
         &campaign_id,
         &String::from_str(&env, "First Milestone"),
         &500,
+        &None,
     );
     assert_eq!(milestone.status, MilestoneStatus::Pending);
 
-    let donation = donation_client.donate(&donor, &campaign_addr, &campaign_id, &600, &None);
+    let donation = donation_client.donate(
+        &donor,
+        &campaign_addr,
+        &campaign_id,
+        &600,
+        &None,
+        &None,
+        &None,
+    );
     assert_eq!(donation.amount, 600);
 
     let docs = vec![
@@ -58,7 +78,8 @@ fn test_full_campaign_flow() {
         String::from_str(&env, "verification.pdf"),
         String::from_str(&env, "photos.zip"),
     ];
-    let verified = verification_client.verify_milestone(&verifier, &campaign_id, &0, &docs);
+    let verified =
+        verification_client.verify_milestone(&verifier, &campaign_id, &0, &docs, &vec![&env]);
     assert_eq!(verified.status, MilestoneStatus::Verified);
 
     let completed = verification_client.complete_milestone(&verifier, &campaign_id, &0);
@@ -70,4 +91,14 @@ fn test_full_campaign_flow() {
 
     let total_donated = donation_client.get_total_donated(&campaign_id);
     assert_eq!(total_donated, 600);
+
+    let export = campaign_client.export(&campaign_id);
+    assert_eq!(
+        export.reference_hashes.get(symbol_short!("milestone")),
+        Some(verification_client.milestones_digest(&campaign_id))
+    );
+    assert_eq!(
+        export.reference_hashes.get(symbol_short!("donations")),
+        Some(donation_client.donations_digest(&campaign_id))
+    );
 }